@@ -3,10 +3,47 @@ use std::{env, error::Error, fs::File, io::Write, path::PathBuf};
 fn main() -> Result<(), Box<dyn Error>> {
     // Put the linker script somewhere the linker can find it
     let out = PathBuf::from(env::var("OUT_DIR")?);
+    let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
 
-    File::create(out.join("stlog.x"))?.write_all(include_bytes!("stlog.x"))?;
+    let mut script = include_str!("stlog.x").to_owned();
+    if target_os != "none" {
+        // On hosted targets `-T` replaces the default linker script; this makes it augment the
+        // default script instead
+        script = script.replacen("\n}\n", "\n} INSERT AFTER .comment;\n", 1);
+    }
+
+    if env::var_os("CARGO_FEATURE_NULL_LOGGER").is_some() {
+        // Fall back to the `NullLogger` if the application doesn't declare a `global_logger`
+        let fallback =
+            "\nEXTERN(__stlog_null_logger);\nPROVIDE(\"stlog::GLOBAL_LOGGER\" = __stlog_null_logger);\n";
+        script.push_str(fallback);
+
+        let family = env::var("CARGO_CFG_TARGET_FAMILY").unwrap_or_default();
+        let vendor = env::var("CARGO_CFG_TARGET_VENDOR").unwrap_or_default();
+        if target_os != "none" && family == "unix" && vendor != "apple" {
+            // ELF linkers treat an input file that's not an object file or an archive as a linker
+            // script that augments the default one; this provides the fallback to applications
+            // that are not linked using `stlog.x`
+            File::create(out.join("libstlog_null_logger.a"))?.write_all(fallback.as_bytes())?;
+            println!("cargo:rustc-link-lib=dylib=stlog_null_logger");
+        }
+    }
+
+    File::create(out.join("stlog.x"))?.write_all(script.as_bytes())?;
+
+    if target_os == "linux" {
+        // so the output of the examples can be decoded and the tests can use per-level loggers
+        for kind in &["examples", "tests"] {
+            println!(
+                "cargo:rustc-link-arg-{}=-T{}",
+                kind,
+                out.join("stlog.x").display()
+            );
+        }
+    }
 
     println!("cargo:rustc-link-search={}", out.display());
+    println!("cargo:rerun-if-changed=stlog.x");
 
     Ok(())
 }
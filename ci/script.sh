@@ -2,35 +2,20 @@ set -euxo pipefail
 
 main() {
     cargo check --target $T
-
-    if [ $TRAVIS_RUST_VERSION = nightly ]; then
-        cargo check --features spanned --target $T
-    fi
+    cargo check --features spanned --target $T
 
     if [ $T = x86_64-unknown-linux-gnu ]; then
         cargo build --examples --target $T
-
-        if [ $TRAVIS_RUST_VERSION = nightly ]; then
-            cargo build --examples --target $T --features spanned
-        fi
+        cargo test --features critical-section --target $T
+        cargo test --features embedded-hal,embedded-io --target $T
+        cargo test --features std --target $T
+        cargo test --features async --target $T
+        cargo test --features null-logger --target $T
+        cargo test -p stlog-decoder --target $T
+        cargo build --examples --target $T --features spanned
     fi
 }
 
-# fake Travis variables to be able to run this on a local machine
-if [ -z ${TRAVIS_RUST_VERSION-} ]; then
-    case $(rustc -V) in
-        *nightly*)
-            TRAVIS_RUST_VERSION=nightly
-            ;;
-        *beta*)
-            TRAVIS_RUST_VERSION=beta
-            ;;
-        *)
-            TRAVIS_RUST_VERSION=stable
-            ;;
-    esac
-fi
-
 if [ -z ${T-} ]; then
     T=$(rustc -Vv | grep host | cut -d ' ' -f2)
 fi
@@ -0,0 +1,156 @@
+use std::time::{Duration, SystemTime};
+
+use crate::{Frame, Record};
+
+/// Lines up device timestamps with the host time at which the records arrived
+///
+/// The host time is modeled as `offset + (1 + drift) * device_time`. Both the offset and the drift
+/// are estimated using a least squares fit over all the records seen since the last device reset,
+/// which averages out the transport latency jitter. Device resets, reported by the decoder as
+/// `Frame::Reset`, start a new fit but the drift estimate, a property of the device oscillator, is
+/// carried over.
+///
+/// # Example
+///
+/// ```
+/// use std::time::{Duration, SystemTime};
+///
+/// use stlog_decoder::{Correlator, Frame, Level, Record};
+///
+/// let record = |ms| Record {
+///     ticks: None,
+///     time: Some(Duration::from_millis(ms)),
+///     depth: 0,
+///     frame: Frame::Message {
+///         level: Level::Info,
+///         message: "Hello".to_owned(),
+///     },
+/// };
+///
+/// let t0 = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+///
+/// // the device clock runs 1% slower than the host clock
+/// let mut correlator = Correlator::new();
+/// correlator.correlate(&record(0), t0);
+/// correlator.correlate(&record(1_000), t0 + Duration::from_millis(1_010));
+/// correlator.correlate(&record(2_000), t0 + Duration::from_millis(2_020));
+///
+/// assert!((correlator.drift() - 0.01).abs() < 1e-9);
+///
+/// let host = correlator.correlate(&record(3_000), t0 + Duration::from_millis(3_030));
+/// assert_eq!(host, Some(t0 + Duration::from_millis(3_030)));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Correlator {
+    /// Device and host time of the first record of the current fit
+    origin: Option<(Duration, SystemTime)>,
+    /// Last device time seen
+    last: Option<Duration>,
+    n: f64,
+    sx: f64,
+    sy: f64,
+    sxx: f64,
+    sxy: f64,
+    /// Drift carried over from the previous fit
+    prior: f64,
+}
+
+impl Correlator {
+    /// Creates a correlator with no samples
+    pub fn new() -> Self {
+        Correlator {
+            origin: None,
+            last: None,
+            n: 0.,
+            sx: 0.,
+            sy: 0.,
+            sxx: 0.,
+            sxy: 0.,
+            prior: 0.,
+        }
+    }
+
+    /// Adds a `record` that arrived at `arrival` to the fit
+    ///
+    /// Returns the estimated host time at which the record was logged, or `None` if the record
+    /// has no device timestamp
+    pub fn correlate(&mut self, record: &Record, arrival: SystemTime) -> Option<SystemTime> {
+        let time = record.time?;
+
+        // also catch resets that the decoder couldn't see, e.g. due to dropped bytes
+        let backwards = self.last.map(|last| time < last).unwrap_or(false);
+        if record.frame == Frame::Reset || backwards {
+            self.restart();
+        }
+        self.last = Some(time);
+
+        let (device0, host0) = *self.origin.get_or_insert((time, arrival));
+        let x = (time - device0).as_secs_f64();
+        let y = signed_secs(arrival, host0);
+
+        self.n += 1.;
+        self.sx += x;
+        self.sy += y;
+        self.sxx += x * x;
+        self.sxy += x * y;
+
+        Some(from_signed_secs(
+            host0,
+            self.offset() + (1. + self.drift()) * x,
+        ))
+    }
+
+    /// Returns the estimated drift of the device clock relative to the host clock
+    ///
+    /// A positive value means that the device clock runs slower than the host clock. Multiply by
+    /// `1e6` to get the drift in ppm.
+    pub fn drift(&self) -> f64 {
+        let den = self.n * self.sxx - self.sx * self.sx;
+        if self.n < 2. || den.abs() < f64::EPSILON {
+            self.prior
+        } else {
+            (self.n * self.sxy - self.sx * self.sy) / den - 1.
+        }
+    }
+
+    /// Returns the estimated host time, relative to the first record of the current fit, at which
+    /// the device time was zero
+    fn offset(&self) -> f64 {
+        if self.n == 0. {
+            0.
+        } else {
+            (self.sy - (1. + self.drift()) * self.sx) / self.n
+        }
+    }
+
+    /// Starts a new fit
+    fn restart(&mut self) {
+        *self = Correlator {
+            prior: self.drift(),
+            ..Correlator::new()
+        };
+    }
+}
+
+impl Default for Correlator {
+    fn default() -> Self {
+        Correlator::new()
+    }
+}
+
+/// Returns `a - b` in seconds
+fn signed_secs(a: SystemTime, b: SystemTime) -> f64 {
+    match a.duration_since(b) {
+        Ok(d) => d.as_secs_f64(),
+        Err(e) => -e.duration().as_secs_f64(),
+    }
+}
+
+/// Returns `base + secs`
+fn from_signed_secs(base: SystemTime, secs: f64) -> SystemTime {
+    if secs >= 0. {
+        base + Duration::from_secs_f64(secs)
+    } else {
+        base - Duration::from_secs_f64(-secs)
+    }
+}
@@ -0,0 +1,616 @@
+use std::{fmt, time::Duration};
+
+use crate::{
+    layout::{Kind, Layout, Shape},
+    Error, Level, Table,
+};
+
+/// Strings of the internal messages emitted by `stlog::Counters::flush`
+const SNAPSHOT: &str = "counters {}";
+const COUNT: &str = "{} counted {} times";
+
+/// String of the internal message emitted by `stlog::Dropped`
+const DROPPED: &str = "{} messages dropped";
+
+/// String of the internal message emitted when a `stlog::span!` is entered
+const ENTER: &str = "enter {}";
+
+/// String of the internal message emitted when a `stlog::span!` is exited
+const EXIT: &str = "exit {}";
+
+/// String of the internal message emitted by `stlog::start_session`
+const SESSION: &str = "session start {}";
+
+/// String of the internal message emitted by the `*_throttled!` macros
+const SUPPRESSED: &str = "{} suppressed {} times";
+
+/// String of the internal message emitted by `stlog::Timestamped`
+const SYNC: &str = "timestamp sync {}";
+
+/// String of the internal message that replaces the records that don't fit in a `stlog::Formatter`
+const TOO_LONG: &str = "record too long";
+
+/// A decoded message
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Frame {
+    /// A message logged by the device
+    Message {
+        /// The severity of the message
+        level: Level,
+        /// The logged string, with its arguments already formatted
+        message: String,
+    },
+    /// The device had to discard this many messages
+    Dropped(u32),
+    /// The device discarded a message whose record didn't fit in its buffer
+    TooLong,
+    /// The device clock went backwards; the device was reset
+    Reset,
+    /// The device started a new session; this is the build ID of its firmware
+    Session(u32),
+    /// A throttled message was suppressed this many times before being logged again
+    Suppressed {
+        /// The severity of the suppressed message
+        level: Level,
+        /// The suppressed message
+        message: String,
+        /// How many times it was suppressed
+        count: u32,
+    },
+    /// A span was entered
+    Enter {
+        /// The severity of the span
+        level: Level,
+        /// The name of the span
+        name: String,
+    },
+    /// A span was exited
+    Exit {
+        /// The severity of the span
+        level: Level,
+        /// The name of the span
+        name: String,
+        /// How long the span lasted
+        ///
+        /// This is only available if the records are timestamped and the matching `Enter` frame
+        /// was decoded
+        duration: Option<Duration>,
+    },
+    /// A snapshot of the message counters kept by `stlog::Counters`
+    Counters(Vec<Counter>),
+}
+
+/// A row of a `Frame::Counters` snapshot
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Counter {
+    /// The severity of the message
+    pub level: Level,
+    /// The counted message
+    pub message: String,
+    /// How many times the message was logged
+    pub count: u32,
+}
+
+impl Frame {
+    /// Formats the frame, indenting the text after the level by `depth` steps
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        let indent = 2 * depth;
+
+        match self {
+            Frame::Message { level, message } => {
+                write!(f, "{:<5} {:indent$}{}", level, "", message, indent = indent)
+            }
+            Frame::Dropped(count) => write!(f, "***** {} messages dropped *****", count),
+            Frame::TooLong => f.write_str("***** record too long *****"),
+            Frame::Reset => f.write_str("***** device reset *****"),
+            Frame::Session(build_id) => {
+                write!(f, "***** session start (build {:#010x}) *****", build_id)
+            }
+            Frame::Suppressed {
+                level,
+                message,
+                count,
+            } => write!(
+                f,
+                "{:<5} {:indent$}({:?} was suppressed {} times)",
+                level,
+                "",
+                message,
+                count,
+                indent = indent
+            ),
+            Frame::Enter { level, name } => {
+                write!(f, "{:<5} {:indent$}-> {}", level, "", name, indent = indent)
+            }
+            Frame::Exit {
+                level,
+                name,
+                duration,
+            } => {
+                write!(f, "{:<5} {:indent$}<- {}", level, "", name, indent = indent)?;
+
+                if let Some(duration) = duration {
+                    write!(f, " ({:?})", duration)?;
+                }
+
+                Ok(())
+            }
+            Frame::Counters(counters) => {
+                f.write_str("***** message counters *****\n")?;
+                write!(f, "{:<5} {:>10} MESSAGE", "LEVEL", "COUNT")?;
+
+                for counter in counters {
+                    write!(
+                        f,
+                        "\n{:<5} {:>10} {}",
+                        counter.level, counter.count, counter.message
+                    )?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+/// A decoded record
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Record {
+    /// Device time, in ticks, at which the message was logged
+    ///
+    /// This is only available if the decoder was configured to expect timestamps
+    pub ticks: Option<u64>,
+    /// Device time at which the message was logged
+    ///
+    /// This is only available if the decoder was configured to expect timestamps
+    pub time: Option<Duration>,
+    /// Number of spans that were active when the message was logged
+    ///
+    /// `Enter` and `Exit` frames have the depth of the span that encloses them.
+    pub depth: usize,
+    /// The message
+    pub frame: Frame,
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(time) = self.time {
+            write!(f, "{:>10}.{:06} ", time.as_secs(), time.subsec_micros())?;
+        }
+
+        self.frame.fmt_indented(f, self.depth)
+    }
+}
+
+/// Decodes a log stream
+///
+/// Bytes can be fed to the decoder as they arrive; partial records are kept around until the rest
+/// of their bytes are `push`-ed.
+pub struct Decoder<'t> {
+    table: &'t Table,
+    buffer: Vec<u8>,
+    tick_rate: Option<u32>,
+    ticks: Option<u64>,
+    /// Active spans: the ID of their name and the device time at which they were entered
+    spans: Vec<(u8, Option<u64>)>,
+    /// Counter snapshot being received: the number of rows left and the rows received so far
+    snapshot: Option<(u32, Vec<Counter>)>,
+}
+
+impl<'t> Decoder<'t> {
+    /// Creates a decoder that looks up messages in the given `table`
+    pub fn new(table: &'t Table) -> Self {
+        Decoder {
+            table,
+            buffer: vec![],
+            tick_rate: None,
+            ticks: None,
+            spans: vec![],
+            snapshot: None,
+        }
+    }
+
+    /// Expects each record to be prefixed with a timestamp, as sent by `stlog::Timestamped`
+    ///
+    /// `tick_rate` is the frequency, in Hz, of the device clock.
+    pub fn timestamped(mut self, tick_rate: u32) -> Self {
+        self.tick_rate = Some(tick_rate);
+        self
+    }
+
+    /// Appends bytes received from the device
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Decodes the next record
+    ///
+    /// Returns `None` if more bytes are needed to decode the next record. On error the offending
+    /// byte is discarded so decoding can resume with the next call.
+    pub fn decode(&mut self) -> Option<Result<Record, Error>> {
+        loop {
+            let Raw {
+                delta,
+                id,
+                args,
+                values,
+                len,
+            } = match self.parse()? {
+                Ok(raw) => raw,
+                Err(e) => {
+                    self.buffer.remove(0);
+                    return Some(Err(e));
+                }
+            };
+            self.buffer.drain(..len);
+
+            let entry = self.table.get(id)?;
+            let mut frame = if entry.level == Level::Internal && entry.string == SYNC {
+                let sync = u64::from(args[0]);
+                match self.ticks {
+                    // the tick counter went backwards
+                    Some(ticks) if sync < ticks & 0xffff_ffff => {
+                        self.ticks = Some(sync);
+                        Frame::Reset
+                    }
+                    // keep the bits above the 32-bit counter
+                    Some(ticks) => {
+                        self.ticks = Some((ticks & !0xffff_ffff) | sync);
+                        continue;
+                    }
+                    None => {
+                        self.ticks = Some(sync);
+                        continue;
+                    }
+                }
+            } else if entry.level == Level::Internal && entry.string == SESSION {
+                let build_id = args[0];
+                match self.table.build_id() {
+                    // 0: the firmware doesn't know its build ID
+                    Some(elf) if build_id != 0 && elf != build_id => {
+                        return Some(Err(Error::BuildIdMismatch {
+                            device: build_id,
+                            elf,
+                        }))
+                    }
+                    _ => Frame::Session(build_id),
+                }
+            } else if entry.level == Level::Internal && entry.string == SUPPRESSED {
+                let id = args[0] as u8;
+                let suppressed = match self.table.get(id) {
+                    Some(suppressed) if args[0] < 256 => suppressed,
+                    _ => return Some(Err(Error::UnknownId(id))),
+                };
+
+                Frame::Suppressed {
+                    level: suppressed.level,
+                    message: suppressed.string.clone(),
+                    count: args[1],
+                }
+            } else if entry.level == Level::Internal
+                && (entry.string == ENTER || entry.string == EXIT)
+            {
+                let id = args[0] as u8;
+                let span = match self.table.get(id) {
+                    Some(span) if args[0] < 256 => span,
+                    _ => return Some(Err(Error::UnknownId(id))),
+                };
+
+                if entry.string == ENTER {
+                    Frame::Enter {
+                        level: span.level,
+                        name: span.string.clone(),
+                    }
+                } else {
+                    Frame::Exit {
+                        level: span.level,
+                        name: span.string.clone(),
+                        duration: None,
+                    }
+                }
+            } else if entry.level == Level::Internal && entry.string == SNAPSHOT {
+                if args[0] != 0 {
+                    self.snapshot = Some((args[0], vec![]));
+                    continue;
+                }
+
+                Frame::Counters(vec![])
+            } else if entry.level == Level::Internal && entry.string == COUNT {
+                let id = args[0] as u8;
+                let counted = match self.table.get(id) {
+                    Some(counted) if args[0] < 256 => counted,
+                    _ => return Some(Err(Error::UnknownId(id))),
+                };
+
+                // a row without a header is treated as a single row snapshot
+                let (left, mut rows) = self.snapshot.take().unwrap_or((1, vec![]));
+                rows.push(Counter {
+                    level: counted.level,
+                    message: counted.string.clone(),
+                    count: args[1],
+                });
+
+                if left > 1 {
+                    self.snapshot = Some((left - 1, rows));
+                    continue;
+                }
+
+                Frame::Counters(rows)
+            } else if entry.level == Level::Internal && entry.string == DROPPED {
+                Frame::Dropped(args[0])
+            } else if entry.level == Level::Internal && entry.string == TOO_LONG {
+                Frame::TooLong
+            } else if entry.level == Level::Internal {
+                Frame::Message {
+                    level: entry.level,
+                    message: format(&entry.string, args.iter().map(|arg| arg.to_string())),
+                }
+            } else {
+                Frame::Message {
+                    level: entry.level,
+                    message: format(&entry.string, values),
+                }
+            };
+
+            let ticks = delta.map(|delta| {
+                let ticks = self.ticks.unwrap_or(0) + u64::from(delta);
+                self.ticks = Some(ticks);
+                ticks
+            });
+            let time = ticks.and_then(|ticks| self.duration(ticks));
+
+            let depth = match &mut frame {
+                Frame::Enter { .. } => {
+                    self.spans.push((args[0] as u8, ticks));
+                    self.spans.len() - 1
+                }
+                Frame::Exit { duration, .. } => {
+                    let span = args[0] as u8;
+                    match self.spans.iter().rposition(|&(id, _)| id == span) {
+                        Some(depth) => {
+                            let (_, enter) = self.spans[depth];
+                            // also close the inner spans whose exit records were lost
+                            self.spans.truncate(depth);
+                            *duration = ticks
+                                .and_then(|exit| exit.checked_sub(enter?))
+                                .and_then(|ticks| self.duration(ticks));
+                            depth
+                        }
+                        // entered before we started decoding
+                        None => self.spans.len(),
+                    }
+                }
+                Frame::Reset | Frame::Session(_) => {
+                    self.spans.clear();
+                    0
+                }
+                _ => self.spans.len(),
+            };
+
+            return Some(Ok(Record {
+                ticks,
+                time,
+                depth,
+                frame,
+            }));
+        }
+    }
+
+    /// Converts device ticks into a duration
+    fn duration(&self, ticks: u64) -> Option<Duration> {
+        let rate = u128::from(self.tick_rate?);
+        Some(Duration::from_nanos(
+            (u128::from(ticks) * 1_000_000_000 / rate) as u64,
+        ))
+    }
+
+    /// Parses the record at the front of the buffer without consuming it
+    fn parse(&self) -> Option<Result<Raw, Error>> {
+        let mut cursor = 0;
+
+        let delta = if self.tick_rate.is_some() {
+            match uleb128(&self.buffer) {
+                Ok(Some((delta, len))) => {
+                    cursor += len;
+                    Some(delta)
+                }
+                Ok(None) => return None,
+                Err(()) => return Some(Err(Error::Malformed(self.buffer[0]))),
+            }
+        } else {
+            None
+        };
+
+        let id = *self.buffer.get(cursor)?;
+        cursor += 1;
+
+        let entry = match self.table.get(id) {
+            Some(entry) => entry,
+            None => return Some(Err(Error::UnknownId(id))),
+        };
+
+        let nargs = entry.string.matches("{}").count();
+
+        // the arguments of internal messages are numbers; those of the application messages are
+        // values sent through `stlog::Format`
+        let mut args = vec![];
+        let mut values = vec![];
+        for _ in 0..nargs {
+            if entry.level == Level::Internal {
+                match uleb128(&self.buffer[cursor..]) {
+                    Ok(Some((arg, len))) => {
+                        args.push(arg);
+                        cursor += len;
+                    }
+                    Ok(None) => return None,
+                    Err(()) => return Some(Err(Error::Malformed(id))),
+                }
+            } else {
+                match self.value(id, &mut cursor)? {
+                    Ok(value) => values.push(value),
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+        }
+
+        Some(Ok(Raw {
+            delta,
+            id,
+            args,
+            values,
+            len: cursor,
+        }))
+    }
+
+    /// Decodes, and formats, the value that starts at `cursor`; `id` is the ID of the message
+    ///
+    /// Returns `None` if more bytes are needed
+    fn value(&self, id: u8, cursor: &mut usize) -> Option<Result<String, Error>> {
+        // an ID takes at most two bytes
+        let arg = match self.varint(id, cursor, 2)? {
+            Ok(arg) => arg,
+            Err(e) => return Some(Err(e)),
+        };
+
+        let layout = match self.table.name(arg as u8) {
+            Some(symbol) if arg < 256 => match Layout::parse(symbol) {
+                Some(layout) => layout,
+                None => return Some(Err(Error::Malformed(id))),
+            },
+            _ => return Some(Err(Error::UnknownId(arg as u8))),
+        };
+
+        let mut s = layout.name.to_owned();
+        match layout.shape {
+            Shape::Unit => {}
+            Shape::Named(fields) => {
+                if fields.is_empty() {
+                    s.push_str(" {}");
+                } else {
+                    s.push_str(" { ");
+                    for (i, (name, kind)) in fields.into_iter().enumerate() {
+                        if i != 0 {
+                            s.push_str(", ");
+                        }
+
+                        s.push_str(name);
+                        s.push_str(": ");
+                        match self.field(id, kind, cursor)? {
+                            Ok(value) => s.push_str(&value),
+                            Err(e) => return Some(Err(e)),
+                        }
+                    }
+                    s.push_str(" }");
+                }
+            }
+            Shape::Tuple(fields) => {
+                s.push('(');
+                for (i, kind) in fields.into_iter().enumerate() {
+                    if i != 0 {
+                        s.push_str(", ");
+                    }
+
+                    match self.field(id, kind, cursor)? {
+                        Ok(value) => s.push_str(&value),
+                        Err(e) => return Some(Err(e)),
+                    }
+                }
+                s.push(')');
+            }
+        }
+
+        Some(Ok(s))
+    }
+
+    /// Decodes, and formats, a field of a value
+    fn field(&self, id: u8, kind: Kind, cursor: &mut usize) -> Option<Result<String, Error>> {
+        Some(Ok(match kind {
+            Kind::Unsigned => match self.varint(id, cursor, 10)? {
+                Ok(n) => n.to_string(),
+                Err(e) => return Some(Err(e)),
+            },
+            Kind::Signed => match self.varint(id, cursor, 10)? {
+                // zigzag
+                Ok(n) => ((n >> 1) as i64 ^ -((n & 1) as i64)).to_string(),
+                Err(e) => return Some(Err(e)),
+            },
+            Kind::Bool => {
+                let b = match *self.buffer.get(*cursor)? {
+                    0 => "false",
+                    1 => "true",
+                    _ => return Some(Err(Error::Malformed(id))),
+                };
+                *cursor += 1;
+                b.to_owned()
+            }
+            Kind::Nested => return self.value(id, cursor),
+        }))
+    }
+
+    /// Decodes an unsigned LEB128 integer that spans at most `max_len` bytes
+    fn varint(&self, id: u8, cursor: &mut usize, max_len: usize) -> Option<Result<u64, Error>> {
+        match leb128(&self.buffer[*cursor..], max_len) {
+            Ok(Some((n, len))) => {
+                *cursor += len;
+                Some(Ok(n))
+            }
+            Ok(None) => None,
+            Err(()) => Some(Err(Error::Malformed(id))),
+        }
+    }
+}
+
+/// A record that has not yet been looked up in the symbol table
+struct Raw {
+    /// Ticks elapsed since the previous record
+    delta: Option<u32>,
+    id: u8,
+    /// Arguments of an internal message
+    args: Vec<u32>,
+    /// Formatted arguments of an application message
+    values: Vec<String>,
+    /// Length of the record in bytes
+    len: usize,
+}
+
+/// Replaces each `{}` in `string` with the corresponding argument
+fn format(string: &str, args: impl IntoIterator<Item = String>) -> String {
+    let mut pieces = string.split("{}");
+    let mut s = pieces.next().unwrap_or("").to_owned();
+    for (piece, arg) in pieces.zip(args) {
+        s.push_str(&arg);
+        s.push_str(piece);
+    }
+    s
+}
+
+/// Decodes an unsigned LEB128 integer
+///
+/// Returns the value and the number of bytes it spans, or `None` if more bytes are needed
+fn uleb128(bytes: &[u8]) -> Result<Option<(u32, usize)>, ()> {
+    leb128(bytes, 5).map(|value| value.map(|(value, len)| (value as u32, len)))
+}
+
+/// Decodes an unsigned LEB128 integer that spans at most `max_len` bytes
+fn leb128(bytes: &[u8], max_len: usize) -> Result<Option<(u64, usize)>, ()> {
+    let mut value = 0u64;
+    for (i, byte) in bytes.iter().enumerate() {
+        if i == max_len {
+            return Err(());
+        }
+
+        value |= u64::from(byte & 0x7f) << (7 * i);
+
+        if byte & 0x80 == 0 {
+            return Ok(Some((value, i + 1)));
+        }
+    }
+
+    Ok(None)
+}
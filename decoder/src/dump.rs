@@ -0,0 +1,147 @@
+//! Post-mortem retrieval of logs from memory dumps
+
+use object::{Endianness, Object, ObjectSegment, ObjectSymbol};
+
+use crate::Error;
+
+/// Marks a valid `stlog::RingBuffer` header; ASCII "STLG"
+const MAGIC: u32 = 0x5354_4c47;
+
+/// Size of the `stlog::RingBuffer` header: magic, size, write index and length
+const HEADER: u64 = 16;
+
+/// A snapshot of the device memory
+#[derive(Clone, Debug, Default)]
+pub struct Memory {
+    regions: Vec<(u64, Vec<u8>)>,
+}
+
+impl Memory {
+    /// Creates a snapshot from a raw dump that starts at address `base`
+    pub fn raw(base: u64, bytes: Vec<u8>) -> Self {
+        Memory {
+            regions: vec![(base, bytes)],
+        }
+    }
+
+    /// Creates a snapshot from the loadable segments of an ELF core file
+    pub fn core(elf: &[u8]) -> Result<Self, Error> {
+        let file = object::File::parse(elf)?;
+
+        let mut regions = vec![];
+        for segment in file.segments() {
+            regions.push((segment.address(), segment.data()?.to_owned()));
+        }
+
+        Ok(Memory { regions })
+    }
+
+    /// Creates a snapshot from either a core file or a raw dump that starts at address `base`
+    pub fn load(base: u64, bytes: Vec<u8>) -> Result<Self, Error> {
+        if bytes.starts_with(b"\x7fELF") {
+            Memory::core(&bytes)
+        } else {
+            Ok(Memory::raw(base, bytes))
+        }
+    }
+
+    /// Reads `len` bytes starting at `address`
+    pub fn read(&self, address: u64, len: u64) -> Option<&[u8]> {
+        self.regions.iter().find_map(|(base, bytes)| {
+            let start = address.checked_sub(*base)?;
+            let end = start.checked_add(len)?;
+            bytes.get(start as usize..end as usize)
+        })
+    }
+}
+
+/// Extracts the contents of the `stlog::RingBuffer` logger from a memory snapshot
+///
+/// `elf` is the device binary; it's used to locate the ring buffer. The returned bytes are in the
+/// order they were logged and can be fed to a [`Decoder`](../struct.Decoder.html).
+///
+/// # Example
+///
+/// ``` no_run
+/// use std::fs;
+///
+/// use stlog_decoder::{
+///     dump::{self, Memory},
+///     Decoder, Table,
+/// };
+///
+/// let elf = fs::read("target/thumbv7m-none-eabi/release/app").unwrap();
+/// let memory = Memory::raw(0x2000_0000, fs::read("ram.bin").unwrap());
+///
+/// let table = Table::parse(&elf).unwrap();
+/// let mut decoder = Decoder::new(&table);
+/// decoder.push(&dump::ring_buffer(&elf, &memory).unwrap());
+///
+/// while let Some(record) = decoder.decode() {
+///     if let Ok(record) = record {
+///         println!("{}", record);
+///     }
+/// }
+/// ```
+pub fn ring_buffer(elf: &[u8], memory: &Memory) -> Result<Vec<u8>, Error> {
+    let file = object::File::parse(elf)?;
+    let endian = file.endianness();
+    let word = if file.is_64() { 8 } else { 4 };
+
+    // `stlog::RING_BUFFER` holds the address of the ring buffer that logged last
+    let symbol = file
+        .symbols()
+        .find(|s| s.name() == Ok("stlog::RING_BUFFER"))
+        .ok_or(Error::MissingSymbol("stlog::RING_BUFFER"))?;
+    let pointer = memory
+        .read(symbol.address(), word)
+        .ok_or(Error::NotInDump(symbol.address()))?;
+    let address = if word == 8 {
+        read_u64(pointer, endian)
+    } else {
+        u64::from(read_u32(pointer, endian))
+    };
+    if address == 0 {
+        // nothing has been logged yet
+        return Ok(vec![]);
+    }
+
+    let header = memory
+        .read(address, HEADER)
+        .ok_or(Error::NotInDump(address))?;
+    let field = |i: usize| read_u32(&header[4 * i..], endian);
+    if field(0) != MAGIC {
+        return Err(Error::NoRingBuffer);
+    }
+
+    let (size, write, len) = (field(1) as usize, field(2) as usize, field(3) as usize);
+    if write >= size.max(1) || len > size {
+        return Err(Error::NoRingBuffer);
+    }
+
+    let buffer = memory
+        .read(address + HEADER, size as u64)
+        .ok_or(Error::NotInDump(address + HEADER))?;
+
+    // the oldest byte is right after the newest one, unless the buffer hasn't wrapped around yet
+    let mut bytes = buffer[write..][..len.saturating_sub(write)].to_vec();
+    bytes.extend_from_slice(&buffer[..write]);
+    Ok(bytes)
+}
+
+fn read_u32(bytes: &[u8], endian: Endianness) -> u32 {
+    let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+    match endian {
+        Endianness::Little => u32::from_le_bytes(bytes),
+        Endianness::Big => u32::from_be_bytes(bytes),
+    }
+}
+
+fn read_u64(bytes: &[u8], endian: Endianness) -> u64 {
+    let mut array = [0; 8];
+    array.copy_from_slice(&bytes[..8]);
+    match endian {
+        Endianness::Little => u64::from_le_bytes(array),
+        Endianness::Big => u64::from_be_bytes(array),
+    }
+}
@@ -0,0 +1,97 @@
+//! Layouts of the types that implement `stlog::Format`
+//!
+//! The layout is the name of the symbol interned by the derive, e.g.
+//! `app::Config { baud: u32, parity: bool }`, `app::Pair(u8, State)` or `app::State::Connecting`
+//! for a variant of a `#[derive(Intern)]` enum.
+
+/// How the value of a field is encoded
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Kind {
+    /// Unsigned LEB128
+    Unsigned,
+    /// Zigzag encoded LEB128
+    Signed,
+    /// A single byte
+    Bool,
+    /// The ID of the layout of the value followed by its fields
+    Nested,
+}
+
+impl Kind {
+    fn from_type(ty: &str) -> Kind {
+        match ty {
+            "u8" | "u16" | "u32" | "u64" | "usize" => Kind::Unsigned,
+            "i8" | "i16" | "i32" | "i64" | "isize" => Kind::Signed,
+            "bool" => Kind::Bool,
+            _ => Kind::Nested,
+        }
+    }
+}
+
+/// The fields of a layout
+#[derive(Debug, Eq, PartialEq)]
+pub enum Shape<'a> {
+    /// A unit struct or an enum variant
+    Unit,
+    /// A struct with named fields
+    Named(Vec<(&'a str, Kind)>),
+    /// A tuple struct
+    Tuple(Vec<Kind>),
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Layout<'a> {
+    /// The name of the type, or of the variant, without its path
+    pub name: &'a str,
+    pub shape: Shape<'a>,
+}
+
+impl<'a> Layout<'a> {
+    /// Parses the name of an interned symbol; returns `None` if it's malformed
+    pub fn parse(symbol: &'a str) -> Option<Self> {
+        let end = symbol.find([' ', '(']).unwrap_or(symbol.len());
+        let (path, fields) = symbol.split_at(end);
+        let name = path.rsplit("::").next().unwrap_or(path);
+
+        let shape = if fields.is_empty() {
+            Shape::Unit
+        } else if let Some(fields) = fields.strip_prefix(" {").and_then(|f| f.strip_suffix('}')) {
+            let mut named = vec![];
+            for field in split(fields) {
+                let colon = field.find(':')?;
+                let ty = field[colon + 1..].trim();
+                named.push((field[..colon].trim(), Kind::from_type(ty)));
+            }
+            Shape::Named(named)
+        } else {
+            let fields = fields.strip_prefix('(')?.strip_suffix(')')?;
+            Shape::Tuple(split(fields).map(Kind::from_type).collect())
+        };
+
+        Some(Layout { name, shape })
+    }
+}
+
+/// Splits a list of fields at the commas that are not nested in the generic arguments of a type
+fn split(fields: &str) -> impl Iterator<Item = &str> {
+    let mut depth = 0;
+    let mut start = 0;
+    let mut pieces = vec![];
+    for (i, c) in fields.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                pieces.push(&fields[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    pieces.push(&fields[start..]);
+
+    pieces
+        .into_iter()
+        .map(str::trim)
+        .filter(|piece| !piece.is_empty())
+}
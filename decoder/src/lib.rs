@@ -0,0 +1,296 @@
+//! Host side decoder of the stlog logging framework
+//!
+//! The device side of `stlog` sends the *address* of a symbol, whose name is the logged string,
+//! instead of the string itself. This crate looks up these addresses in the `.stlog` section of
+//! the device's ELF file and turns the byte stream back into messages.
+//!
+//! # Example
+//!
+//! ```
+//! use stlog_decoder::{Decoder, Frame, Level, Table};
+//!
+//! // normally this is parsed from the device binary using `Table::parse`
+//! let mut table = Table::new();
+//! table.insert(0, Level::Internal, "{} messages dropped");
+//! table.insert(1, Level::Info, "Hello, world!");
+//!
+//! let mut decoder = Decoder::new(&table);
+//! decoder.push(&[1, 0, 3, 1]);
+//!
+//! let mut records = vec![];
+//! while let Some(record) = decoder.decode() {
+//!     records.push(record.unwrap().to_string());
+//! }
+//!
+//! assert_eq!(
+//!     records,
+//!     [
+//!         "INFO  Hello, world!",
+//!         "***** 3 messages dropped *****",
+//!         "INFO  Hello, world!",
+//!     ]
+//! );
+//! ```
+//!
+//! Messages logged using the `*_throttled!` macros may be followed by a `Frame::Suppressed` that
+//! says how many times they were suppressed.
+//!
+//! ```
+//! use stlog_decoder::{Decoder, Level, Table};
+//!
+//! let mut table = Table::new();
+//! table.insert(0, Level::Internal, "{} suppressed {} times");
+//! table.insert(1, Level::Error, "FIFO overrun");
+//!
+//! let mut decoder = Decoder::new(&table);
+//! decoder.push(&[1, 0, 1, 99]);
+//!
+//! assert_eq!(decoder.decode().unwrap().unwrap().to_string(), "ERROR FIFO overrun");
+//! assert_eq!(
+//!     decoder.decode().unwrap().unwrap().to_string(),
+//!     "ERROR (\"FIFO overrun\" was suppressed 99 times)"
+//! );
+//! ```
+//!
+//! ## Timestamps
+//!
+//! If the device uses `stlog::Timestamped` the decoder must be told the tick rate of the device
+//! clock.
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use stlog_decoder::{Decoder, Level, Table};
+//!
+//! let mut table = Table::new();
+//! table.insert(0, Level::Internal, "timestamp sync {}");
+//! table.insert(1, Level::Info, "Hello, world!");
+//!
+//! // 1 kHz clock
+//! let mut decoder = Decoder::new(&table).timestamped(1_000);
+//! // sync (t = 1000), message (+0), message (+500)
+//! decoder.push(&[0, 0, 0xe8, 0x07, 0, 1, 0xf4, 0x03, 1]);
+//!
+//! let first = decoder.decode().unwrap().unwrap();
+//! assert_eq!(first.ticks, Some(1_000));
+//! assert_eq!(first.time, Some(Duration::from_secs(1)));
+//!
+//! let second = decoder.decode().unwrap().unwrap();
+//! assert_eq!(second.time, Some(Duration::from_millis(1_500)));
+//! assert_eq!(second.to_string(), "         1.500000 INFO  Hello, world!");
+//! ```
+//!
+//! When the device is reset a new "timestamp sync" record is sent. The decoder reports a sync that
+//! moves the device clock backwards as a `Frame::Reset`.
+//!
+//! ## Spans
+//!
+//! The records sent by `stlog::span!` are decoded as `Frame::Enter` and `Frame::Exit`. Messages
+//! logged while a span is active are indented and, if the records are timestamped, the `Exit`
+//! frame reports how long the span lasted.
+//!
+//! ```
+//! use stlog_decoder::{Decoder, Level, Table};
+//!
+//! let mut table = Table::new();
+//! table.insert(0, Level::Internal, "timestamp sync {}");
+//! table.insert(1, Level::Internal, "enter {}");
+//! table.insert(2, Level::Internal, "exit {}");
+//! table.insert(3, Level::Debug, "flash_erase");
+//! table.insert(4, Level::Info, "Erasing flash");
+//!
+//! let mut decoder = Decoder::new(&table).timestamped(1_000);
+//! // sync (t = 1000), enter (+0), message (+5), exit (+48)
+//! decoder.push(&[0, 0, 0xe8, 0x07, 0, 1, 3, 5, 4, 48, 2, 3]);
+//!
+//! let mut records = vec![];
+//! while let Some(record) = decoder.decode() {
+//!     records.push(record.unwrap().to_string());
+//! }
+//!
+//! assert_eq!(
+//!     records,
+//!     [
+//!         "         1.000000 DEBUG -> flash_erase",
+//!         "         1.005000 INFO    Erasing flash",
+//!         "         1.053000 DEBUG <- flash_erase (53ms)",
+//!     ]
+//! );
+//! ```
+//!
+//! ## Counters
+//!
+//! A snapshot sent by `stlog::Counters::flush` is decoded as a single `Frame::Counters` that's
+//! displayed as a table.
+//!
+//! ```
+//! use stlog_decoder::{Decoder, Level, Table};
+//!
+//! let mut table = Table::new();
+//! table.insert(0, Level::Internal, "counters {}");
+//! table.insert(1, Level::Internal, "{} counted {} times");
+//! table.insert(2, Level::Warn, "Buffer full");
+//! table.insert(3, Level::Info, "Sample acquired");
+//!
+//! let mut decoder = Decoder::new(&table);
+//! // header (2 rows), 2 x 3, 3 x 1000
+//! decoder.push(&[0, 2, 1, 2, 3, 1, 3, 0xe8, 0x07]);
+//!
+//! assert_eq!(
+//!     decoder.decode().unwrap().unwrap().to_string(),
+//!     "***** message counters *****
+//! LEVEL      COUNT MESSAGE
+//! WARN           3 Buffer full
+//! INFO        1000 Sample acquired"
+//! );
+//! ```
+//!
+//! ## Firmware identity
+//!
+//! If the device calls `stlog::start_session`, or logs through a `stlog::Session`, the decoder
+//! checks that the build ID it sends matches the GNU build ID of the ELF file. A mismatch is
+//! reported as an `Error::BuildIdMismatch`; the messages that follow can't be trusted as they'll be
+//! looked up in the wrong symbol table. A device that doesn't know its build ID sends 0, which
+//! skips the check.
+//!
+//! ```
+//! use stlog_decoder::{Decoder, Error, Frame, Level, Table};
+//!
+//! let mut table = Table::new();
+//! table.insert(0, Level::Internal, "session start {}");
+//! table.set_build_id(Some(42));
+//!
+//! let mut decoder = Decoder::new(&table);
+//! decoder.push(&[0, 42, 0, 7]);
+//!
+//! assert_eq!(decoder.decode().unwrap().unwrap().frame, Frame::Session(42));
+//! match decoder.decode().unwrap() {
+//!     Err(Error::BuildIdMismatch { device: 7, elf: 42 }) => {}
+//!     _ => unreachable!(),
+//! }
+//! ```
+//!
+//! # `stlog-decode`
+//!
+//! This crate also provides a command line tool that decodes a log stream read from a file, or
+//! from the standard input if no file is given. Pass `-t <tick rate>` if the stream contains
+//! timestamps; adding `-c` also prints the host time at which each message was logged, estimated
+//! from the arrival time of the records using a [`Correlator`]. `stlog-decode` refuses to decode a
+//! stream produced by a different firmware build unless `-f` is passed.
+//!
+//! ``` text
+//! $ cat /dev/ttyUSB0 | stlog-decode -e /path/to/device/binary
+//! INFO  Hello, world!
+//! ***** 3 messages dropped *****
+//! WARN  The quick brown fox jumps over the lazy dog
+//! ```
+//!
+//! `stlog-decode -e <elf> -x` prints the symbol table as a sidecar file (see
+//! [`Table::to_sidecar`]). `-e` also accepts sidecar files so stripped production binaries can be
+//! decoded.
+//!
+//! ``` text
+//! $ stlog-decode -e /path/to/device/binary -x > app-v1.2.0.stlog.json
+//! $ cat /dev/ttyUSB0 | stlog-decode -e app-v1.2.0.stlog.json
+//! ```
+//!
+//! The records kept in RAM by a `stlog::RingBuffer` logger can be decoded from a raw RAM dump (pass
+//! its start address with `-b`, in decimal or in hexadecimal with a `0x` prefix) or from an ELF core
+//! file. `-n` limits the output to the last N records and `-t` decodes the timestamps of records
+//! logged through a `stlog::Timestamped` wrapper. `-c` is not supported in this mode.
+//!
+//! ``` text
+//! $ stlog-decode -e /path/to/device/binary -m ram.bin -b 0x20000000 -n 3
+//! INFO  Erasing flash
+//! ERROR Flash erase timed out
+//! ERROR Hard fault
+//! ```
+
+#![deny(rust_2018_compatibility)]
+#![deny(rust_2018_idioms)]
+#![deny(warnings)]
+
+use std::{error, fmt};
+
+pub use crate::{
+    correlate::Correlator,
+    decoder::{Counter, Decoder, Frame, Record},
+    table::{Entry, Level, Table},
+};
+
+mod correlate;
+mod decoder;
+pub mod dump;
+mod layout;
+mod sidecar;
+mod table;
+
+/// Errors that can occur while parsing a symbol table or decoding a log stream
+#[derive(Debug)]
+pub enum Error {
+    /// The ELF file could not be parsed
+    Object(object::Error),
+    /// The sidecar file could not be parsed
+    Sidecar(serde_json::Error),
+    /// The sidecar file uses a format version this decoder doesn't understand
+    UnsupportedSidecar(u32),
+    /// An entry of the sidecar file has a level this decoder doesn't know
+    InvalidLevel(String),
+    /// The ELF file has no `.stlog` section; it was not linked with `-Tstlog.x`
+    MissingSection,
+    /// A symbol required to decode the log stream was not found in the ELF file
+    MissingSymbol(&'static str),
+    /// The device sent an ID that's not in the symbol table
+    UnknownId(u8),
+    /// The arguments of a message could not be decoded
+    Malformed(u8),
+    /// The memory dump doesn't contain this address
+    NotInDump(u64),
+    /// `stlog::RING_BUFFER` doesn't point to a `stlog::RingBuffer`
+    NoRingBuffer,
+    /// The stream was produced by a different firmware build than the one in the ELF file
+    BuildIdMismatch {
+        /// Build ID sent by the device
+        device: u32,
+        /// Build ID found in the ELF file
+        elf: u32,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Object(e) => write!(f, "couldn't parse ELF file: {}", e),
+            Error::Sidecar(e) => write!(f, "couldn't parse sidecar file: {}", e),
+            Error::UnsupportedSidecar(v) => write!(f, "unsupported sidecar format version {}", v),
+            Error::InvalidLevel(level) => write!(f, "unknown level `{}` in sidecar file", level),
+            Error::MissingSection => f.write_str("section `.stlog` not found"),
+            Error::MissingSymbol(name) => write!(f, "symbol `{}` not found", name),
+            Error::UnknownId(id) => write!(f, "unknown message ID {}", id),
+            Error::Malformed(id) => write!(f, "malformed arguments for message ID {}", id),
+            Error::NotInDump(address) => {
+                write!(f, "address {:#010x} is not in the memory dump", address)
+            }
+            Error::NoRingBuffer => f.write_str("no `stlog::RingBuffer` found in the memory dump"),
+            Error::BuildIdMismatch { device, elf } => write!(
+                f,
+                "the device is running build {:#010x} but the ELF file is build {:#010x}",
+                device, elf
+            ),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+impl From<object::Error> for Error {
+    fn from(e: object::Error) -> Self {
+        Error::Object(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Sidecar(e)
+    }
+}
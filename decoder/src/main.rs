@@ -0,0 +1,181 @@
+use std::{
+    env,
+    error::Error,
+    fs::{self, File},
+    io::{self, Read},
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use stlog_decoder::{
+    dump::{self, Memory},
+    Correlator, Decoder, Error as DecodeError, Table,
+};
+
+const USAGE: &str =
+    "usage: stlog-decode -e <elf | sidecar> [-x] [-f] [-t <tick rate> [-c]] [-m <dump> [-b <base>] [-n <count>]] [<file>]";
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    let mut elf = None;
+    let mut input = None;
+    let mut tick_rate = None;
+    let mut correlate = false;
+    let mut force = false;
+    let mut export = false;
+    let mut dump = None;
+    let mut base = 0;
+    let mut last = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match &*arg {
+            "-e" => elf = Some(args.next().ok_or(USAGE)?),
+            "-t" => tick_rate = Some(args.next().ok_or(USAGE)?.parse()?),
+            "-c" => correlate = true,
+            "-f" => force = true,
+            "-x" => export = true,
+            "-m" => dump = Some(args.next().ok_or(USAGE)?),
+            "-b" => {
+                let arg = args.next().ok_or(USAGE)?;
+                base = match arg.strip_prefix("0x") {
+                    Some(hex) => u64::from_str_radix(hex, 16)?,
+                    None => arg.parse()?,
+                };
+            }
+            "-n" => last = Some(args.next().ok_or(USAGE)?.parse()?),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            _ if input.is_none() => input = Some(arg),
+            _ => return Err(USAGE.into()),
+        }
+    }
+
+    if correlate && tick_rate.is_none() {
+        return Err("`-c` requires the tick rate (`-t`) of the device clock".into());
+    }
+
+    if correlate && dump.is_some() {
+        return Err("`-c` can't be used with a memory dump (`-m`)".into());
+    }
+
+    let elf = fs::read(elf.ok_or(USAGE)?)?;
+    let table = Table::load(&elf)?;
+
+    if export {
+        println!("{}", table.to_sidecar());
+        return Ok(());
+    }
+
+    if let Some(path) = dump {
+        let memory = Memory::load(base, fs::read(path)?)?;
+
+        let mut decoder = Decoder::new(&table);
+        if let Some(tick_rate) = tick_rate {
+            decoder = decoder.timestamped(tick_rate);
+        }
+        decoder.push(&dump::ring_buffer(&elf, &memory)?);
+
+        let mut records = vec![];
+        while let Some(record) = decoder.decode() {
+            match record {
+                Ok(record) => records.push(record),
+                Err(e @ DecodeError::BuildIdMismatch { .. }) if !force => {
+                    return Err(format!("{} (pass `-f` to decode anyways)", e).into())
+                }
+                // the oldest record may have been partially overwritten
+                Err(DecodeError::UnknownId(_)) | Err(DecodeError::Malformed(_))
+                    if records.is_empty() => {}
+                Err(e) => eprintln!("warning: {}", e),
+            }
+        }
+
+        let skip = records.len().saturating_sub(last.unwrap_or(records.len()));
+        for record in &records[skip..] {
+            println!("{}", record);
+        }
+
+        return Ok(());
+    }
+
+    let mut reader: Box<dyn Read> = match input {
+        Some(path) => Box::new(File::open(path)?),
+        None => Box::new(io::stdin()),
+    };
+
+    let mut decoder = Decoder::new(&table);
+    if let Some(tick_rate) = tick_rate {
+        decoder = decoder.timestamped(tick_rate);
+    }
+    let mut correlator = Correlator::new();
+    let mut buffer = [0; 256];
+    loop {
+        let n = reader.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+
+        let arrival = SystemTime::now();
+        decoder.push(&buffer[..n]);
+        while let Some(record) = decoder.decode() {
+            match record {
+                Ok(record) => {
+                    if correlate {
+                        if let Some(host) = correlator.correlate(&record, arrival) {
+                            print!("{} ", utc(host));
+                        }
+                    }
+
+                    println!("{}", record)
+                }
+                Err(e @ DecodeError::BuildIdMismatch { .. }) if !force => {
+                    return Err(format!("{} (pass `-f` to decode anyways)", e).into())
+                }
+                Err(e) => eprintln!("warning: {}", e),
+            }
+        }
+    }
+
+    if correlate {
+        eprintln!("device clock drift: {:+.3} ppm", correlator.drift() * 1e6);
+    }
+
+    Ok(())
+}
+
+/// Formats `time` as an ISO 8601 UTC timestamp
+fn utc(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+
+    // days since the epoch to a civil date; see http://howardhinnant.github.io/date_algorithms.html
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}Z",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        rem / 60 % 60,
+        rem % 60,
+        since_epoch.subsec_micros()
+    )
+}
@@ -0,0 +1,121 @@
+//! Standalone copy of the symbol table
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Level, Table};
+
+/// Version of the sidecar format; bump on breaking changes
+///
+/// - Version 2 added the interned `names`
+const VERSION: u32 = 2;
+
+/// Oldest version of the sidecar format that can still be loaded
+const MIN_VERSION: u32 = 1;
+
+#[derive(Deserialize, Serialize)]
+struct Sidecar {
+    version: u32,
+    build_id: Option<u32>,
+    entries: Vec<Entry>,
+    /// Missing in version 1 files
+    #[serde(default)]
+    names: Vec<Name>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Entry {
+    id: u8,
+    level: String,
+    string: String,
+    location: Option<Location>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Name {
+    id: u8,
+    name: String,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Location {
+    file: String,
+    line: u32,
+}
+
+pub fn to_json(table: &Table) -> String {
+    let sidecar = Sidecar {
+        version: VERSION,
+        build_id: table.build_id(),
+        entries: table
+            .iter()
+            .map(|(id, entry)| Entry {
+                id,
+                level: level_to_str(entry.level).to_owned(),
+                string: entry.string.clone(),
+                location: entry.location().map(|(file, line)| Location {
+                    file: file.to_owned(),
+                    line,
+                }),
+            })
+            .collect(),
+        names: table
+            .names()
+            .map(|(id, name)| Name {
+                id,
+                name: name.to_owned(),
+            })
+            .collect(),
+    };
+
+    serde_json::to_string_pretty(&sidecar).expect("sidecar serialization can't fail")
+}
+
+pub fn from_json(json: &[u8]) -> Result<Table, Error> {
+    // check the version first; other fields may change between versions
+    #[derive(Deserialize)]
+    struct Version {
+        version: u32,
+    }
+
+    let version = serde_json::from_slice::<Version>(json)?.version;
+    if !(MIN_VERSION..=VERSION).contains(&version) {
+        return Err(Error::UnsupportedSidecar(version));
+    }
+
+    let sidecar = serde_json::from_slice::<Sidecar>(json)?;
+    let mut table = Table::new();
+    table.set_build_id(sidecar.build_id);
+    for entry in sidecar.entries {
+        let level = level_from_str(&entry.level).ok_or(Error::InvalidLevel(entry.level))?;
+        table.insert(entry.id, level, entry.string);
+    }
+
+    for name in sidecar.names {
+        table.insert_name(name.id, name.name);
+    }
+
+    Ok(table)
+}
+
+fn level_to_str(level: Level) -> &'static str {
+    match level {
+        Level::Internal => "internal",
+        Level::Error => "error",
+        Level::Warn => "warn",
+        Level::Info => "info",
+        Level::Debug => "debug",
+        Level::Trace => "trace",
+    }
+}
+
+fn level_from_str(s: &str) -> Option<Level> {
+    Some(match s {
+        "internal" => Level::Internal,
+        "error" => Level::Error,
+        "warn" => Level::Warn,
+        "info" => Level::Info,
+        "debug" => Level::Debug,
+        "trace" => Level::Trace,
+        _ => return None,
+    })
+}
@@ -0,0 +1,288 @@
+use std::{collections::BTreeMap, fmt};
+
+use object::{Object, ObjectSection, ObjectSymbol};
+
+use crate::{sidecar, Error};
+
+/// Severity of a message
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Level {
+    /// Messages emitted by `stlog` itself, e.g. "N messages dropped"
+    Internal,
+    /// `error!`
+    Error,
+    /// `warn!`
+    Warn,
+    /// `info!`
+    Info,
+    /// `debug!`
+    Debug,
+    /// `trace!`
+    Trace,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Level::Internal => "STLOG",
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        };
+
+        f.pad(s)
+    }
+}
+
+/// An entry of the symbol table
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Entry {
+    /// The severity of the message
+    pub level: Level,
+    /// The logged string
+    pub string: String,
+}
+
+impl Entry {
+    /// Returns the file and line of the macro invocation that logs this string
+    ///
+    /// This is only available for strings logged using the `stlog::spanned` macros
+    pub fn location(&self) -> Option<(&str, u32)> {
+        let loc = &self.string[self.string.rfind(", loc: ")? + ", loc: ".len()..];
+        let colon = loc.rfind(':')?;
+        Some((&loc[..colon], loc[colon + 1..].parse().ok()?))
+    }
+}
+
+/// The table of strings that the device can log, indexed by message ID
+#[derive(Clone, Debug, Default)]
+pub struct Table {
+    entries: BTreeMap<u8, Entry>,
+    /// Names interned using `#[derive(stlog::Intern)]` and `#[derive(stlog::Format)]`; they
+    /// describe the arguments of the messages
+    names: BTreeMap<u8, String>,
+    build_id: Option<u32>,
+}
+
+/// Symbols that mark the start of each log level in the `.stlog` section
+const LEVELS: [(&str, Level); 4] = [
+    ("__stlog_warning_start__", Level::Warn),
+    ("__stlog_info_start__", Level::Info),
+    ("__stlog_debug_start__", Level::Debug),
+    ("__stlog_trace_start__", Level::Trace),
+];
+
+impl Table {
+    /// Creates an empty table
+    pub fn new() -> Self {
+        Table::default()
+    }
+
+    /// Loads the table from either an ELF file or a sidecar file
+    pub fn load(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.starts_with(b"\x7fELF") {
+            Table::parse(bytes)
+        } else {
+            Table::from_sidecar(bytes)
+        }
+    }
+
+    /// Extracts the table from the `.stlog` section of the given ELF file
+    pub fn parse(elf: &[u8]) -> Result<Self, Error> {
+        let file = object::File::parse(elf)?;
+        let section = file
+            .section_by_name(".stlog")
+            .ok_or(Error::MissingSection)?;
+
+        // the internal messages were added later on; older binaries start at the error level
+        let mut error_start = 0;
+        // as are the interned names
+        let mut intern_start = None;
+        let mut starts = [None; 4];
+        let mut symbols = vec![];
+        for symbol in file.symbols() {
+            if symbol.section_index() != Some(section.index()) {
+                continue;
+            }
+
+            let name = symbol.name()?;
+            let offset = symbol.address() - section.address();
+            if name == "__stlog_error_start__" {
+                error_start = offset;
+            } else if name == "__stlog_intern_start__" {
+                intern_start = Some(offset);
+            } else if let Some(i) = LEVELS.iter().position(|(s, _)| *s == name) {
+                starts[i] = Some(offset);
+            } else if symbol.size() == 0 {
+                // the anchor that marks the start of the section; not a message
+                continue;
+            } else if offset < 256 {
+                // anything that follows an `@` is the symbol version, not part of the string
+                let string = name.split('@').next().unwrap_or(name);
+                symbols.push((offset, string));
+            }
+        }
+
+        let mut levels = vec![(error_start, Level::Error)];
+        for (start, (name, level)) in starts.iter().zip(LEVELS.iter()) {
+            levels.push((start.ok_or(Error::MissingSymbol(name))?, *level));
+        }
+
+        let mut table = Table::new();
+        table.build_id = build_id(&file)?;
+        for (offset, string) in symbols {
+            if intern_start.map(|start| offset >= start).unwrap_or(false) && offset < error_start {
+                table.insert_name(offset as u8, string);
+                continue;
+            }
+
+            let level = levels
+                .iter()
+                .rev()
+                .find(|(start, _)| offset >= *start)
+                .map(|(_, level)| *level)
+                .unwrap_or(Level::Internal);
+
+            table.insert(offset as u8, level, string);
+        }
+
+        Ok(table)
+    }
+
+    /// Adds an entry to the table
+    pub fn insert(&mut self, id: u8, level: Level, string: impl Into<String>) {
+        self.entries.insert(
+            id,
+            Entry {
+                level,
+                string: string.into(),
+            },
+        );
+    }
+
+    /// Adds a name interned using `#[derive(stlog::Intern)]` or `#[derive(stlog::Format)]`
+    ///
+    /// ```
+    /// use stlog_decoder::{Decoder, Level, Table};
+    ///
+    /// let mut table = Table::new();
+    /// table.insert_name(0, "app::State::Connecting");
+    /// table.insert_name(1, "app::Config { baud: u32, parity: bool }");
+    /// table.insert(2, Level::Info, "state = {}");
+    /// table.insert(3, Level::Info, "config = {}");
+    ///
+    /// let mut decoder = Decoder::new(&table);
+    /// // state (Connecting), config (115200, false)
+    /// decoder.push(&[2, 0, 3, 1, 0x80, 0x84, 0x07, 0]);
+    ///
+    /// assert_eq!(
+    ///     decoder.decode().unwrap().unwrap().to_string(),
+    ///     "INFO  state = Connecting"
+    /// );
+    /// assert_eq!(
+    ///     decoder.decode().unwrap().unwrap().to_string(),
+    ///     "INFO  config = Config { baud: 115200, parity: false }"
+    /// );
+    /// ```
+    pub fn insert_name(&mut self, id: u8, name: impl Into<String>) {
+        self.names.insert(id, name.into());
+    }
+
+    /// Loads a table previously exported using `to_sidecar`
+    ///
+    /// Files exported by older versions of the decoder can still be loaded.
+    ///
+    /// ```
+    /// use stlog_decoder::{Error, Table};
+    ///
+    /// // version 1 files have no interned `names`
+    /// let json = br#"{
+    ///     "version": 1,
+    ///     "build_id": null,
+    ///     "entries": [{ "id": 0, "level": "info", "string": "Hello", "location": null }]
+    /// }"#;
+    ///
+    /// assert!(Table::from_sidecar(json).is_ok());
+    ///
+    /// let json = br#"{
+    ///     "version": 2,
+    ///     "build_id": null,
+    ///     "entries": [{ "id": 0, "level": "fatal", "string": "Oops", "location": null }],
+    ///     "names": []
+    /// }"#;
+    ///
+    /// match Table::from_sidecar(json) {
+    ///     Err(Error::InvalidLevel(level)) => assert_eq!(level, "fatal"),
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    pub fn from_sidecar(json: &[u8]) -> Result<Self, Error> {
+        sidecar::from_json(json)
+    }
+
+    /// Exports the table as a standalone, versioned, JSON document
+    ///
+    /// Production binaries are usually stripped; the sidecar file contains all the information
+    /// needed to decode their logs and can be archived along with each release.
+    ///
+    /// ```
+    /// use stlog_decoder::{Level, Table};
+    ///
+    /// let mut table = Table::new();
+    /// table.insert(0, Level::Warn, "Low battery, loc: src/main.rs:42");
+    /// table.set_build_id(Some(0xdead_beef));
+    ///
+    /// let sidecar = table.to_sidecar();
+    /// let copy = Table::load(sidecar.as_bytes()).unwrap();
+    ///
+    /// assert_eq!(copy.build_id(), Some(0xdead_beef));
+    /// assert_eq!(copy.get(0), table.get(0));
+    /// assert_eq!(copy.get(0).unwrap().location(), Some(("src/main.rs", 42)));
+    /// ```
+    pub fn to_sidecar(&self) -> String {
+        sidecar::to_json(self)
+    }
+
+    /// Returns the build ID of the firmware, the first 4 bytes of its GNU build ID, if any
+    pub fn build_id(&self) -> Option<u32> {
+        self.build_id
+    }
+
+    /// Sets the build ID
+    pub fn set_build_id(&mut self, build_id: Option<u32>) {
+        self.build_id = build_id;
+    }
+
+    /// Looks up the entry with the given message ID
+    pub fn get(&self, id: u8) -> Option<&Entry> {
+        self.entries.get(&id)
+    }
+
+    /// Iterates over the entries of the table in ID order
+    pub fn iter(&self) -> impl Iterator<Item = (u8, &Entry)> {
+        self.entries.iter().map(|(id, entry)| (*id, entry))
+    }
+
+    /// Looks up the interned name with the given ID
+    ///
+    /// The name is the path of an enum variant, e.g. `app::State::Connecting`, or the layout of a
+    /// struct, e.g. `app::Config { baud: u32, parity: bool }`.
+    pub fn name(&self, id: u8) -> Option<&str> {
+        self.names.get(&id).map(|name| &**name)
+    }
+
+    /// Iterates over the interned names in ID order
+    pub fn names(&self) -> impl Iterator<Item = (u8, &str)> {
+        self.names.iter().map(|(id, name)| (*id, &**name))
+    }
+}
+
+/// Reads the first 4 bytes of the GNU build ID, which is what `stlog::start_session` sends
+fn build_id(file: &object::File<'_>) -> Result<Option<u32>, Error> {
+    Ok(file
+        .build_id()?
+        .filter(|id| id.len() >= 4)
+        .map(|id| u32::from_le_bytes([id[0], id[1], id[2], id[3]])))
+}
@@ -0,0 +1,55 @@
+//! Logs a struct; only the values of its fields are sent
+//!
+//! `cargo run --example format > config.bin`
+
+#![deny(warnings)]
+
+use std::io::{self, Write};
+
+use stlog::{global_logger, info, Format, GlobalLog, Intern};
+
+struct Stdout;
+
+impl GlobalLog for Stdout {
+    fn log(&self, byte: u8) {
+        self.log_bytes(&[byte])
+    }
+
+    fn log_bytes(&self, bytes: &[u8]) {
+        io::stdout().write_all(bytes).ok();
+    }
+}
+
+#[global_logger]
+static LOGGER: Stdout = Stdout;
+
+#[derive(Clone, Copy, Intern)]
+enum Parity {
+    None,
+    Even,
+}
+
+#[derive(Format)]
+struct Config {
+    baud: u32,
+    parity: Parity,
+    flow_control: bool,
+}
+
+#[derive(Format)]
+struct Offset(i16, i16);
+
+fn main() {
+    let config = Config {
+        baud: 115_200,
+        parity: Parity::None,
+        flow_control: false,
+    };
+    info!("config = {}", config);
+
+    let config = Config {
+        parity: Parity::Even,
+        ..config
+    };
+    info!("reconfigured to {} with offset {}", config, Offset(-3, 200));
+}
@@ -1,15 +1,23 @@
-#![cfg_attr(feature = "spanned", feature(proc_macro_hygiene))]
+#![deny(warnings)]
+
+use std::io::{self, Write};
 
 #[cfg(feature = "spanned")]
 use stlog::spanned::{error, info, trace};
 #[cfg(not(feature = "spanned"))]
 use stlog::{error, info};
-use stlog::{global_logger, GlobalLog};
+use stlog::{global_logger, span, warn_every_n, warn_throttled, GlobalLog, Level};
 
 struct Logger;
 
 impl GlobalLog for Logger {
-    fn log(&self, _: u8) {}
+    fn log(&self, byte: u8) {
+        self.log_bytes(&[byte])
+    }
+
+    fn log_bytes(&self, bytes: &[u8]) {
+        io::stdout().write_all(bytes).ok();
+    }
 }
 
 #[global_logger]
@@ -19,5 +27,11 @@ fn main() {
     info!("Hello!");
     #[cfg(feature = "spanned")]
     trace!("Hello!");
+    for now in 0..10 {
+        let _span = span!(Level::Debug, "loop");
+
+        warn_every_n!(3, "Every third");
+        warn_throttled!(now, 5, "At most once every 5 ticks");
+    }
     error!("Bye!");
 }
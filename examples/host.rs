@@ -0,0 +1,24 @@
+//! Writes a log file that can be decoded on the host
+//!
+//! ``` text
+//! $ cargo run --example host --features std
+//! $ stlog-decode -e target/debug/examples/host stlog.bin
+//! ```
+
+#![deny(warnings)]
+
+use std::io;
+
+use stlog::{error, global_logger, info, FileLogger};
+
+#[global_logger]
+static LOGGER: FileLogger = FileLogger::new();
+
+fn main() -> io::Result<()> {
+    LOGGER.create("stlog.bin")?;
+
+    info!("Hello!");
+    error!("Bye!");
+
+    Ok(())
+}
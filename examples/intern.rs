@@ -0,0 +1,52 @@
+//! Logs the transitions of a state machine; the states are sent as one byte IDs
+//!
+//! `cargo run --example intern > states.bin`
+
+#![deny(warnings)]
+
+use std::io::{self, Write};
+
+use stlog::{global_logger, info, warn, GlobalLog, Intern};
+
+struct Stdout;
+
+impl GlobalLog for Stdout {
+    fn log(&self, byte: u8) {
+        self.log_bytes(&[byte])
+    }
+
+    fn log_bytes(&self, bytes: &[u8]) {
+        io::stdout().write_all(bytes).ok();
+    }
+}
+
+#[global_logger]
+static LOGGER: Stdout = Stdout;
+
+#[derive(Clone, Copy, Intern)]
+enum State {
+    Idle,
+    Connecting,
+    Connected,
+}
+
+impl State {
+    fn next(self) -> State {
+        match self {
+            State::Idle => State::Connecting,
+            State::Connecting => State::Connected,
+            State::Connected => State::Idle,
+        }
+    }
+}
+
+fn main() {
+    let mut state = State::Idle;
+    for _ in 0..3 {
+        let next = state.next();
+        info!("{} -> {}", state, next);
+        state = next;
+    }
+
+    warn!("stopped in state {}", state);
+}
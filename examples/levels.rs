@@ -0,0 +1,45 @@
+//! Errors are written to stderr and all the other messages to stdout
+//!
+//! `cargo run --example levels 2>errors.bin >others.bin`
+
+#![deny(warnings)]
+
+use std::io::{self, Write};
+
+use stlog::{error, global_logger, info, warn, GlobalLog};
+
+struct Stdout;
+
+impl GlobalLog for Stdout {
+    fn log(&self, byte: u8) {
+        self.log_bytes(&[byte])
+    }
+
+    fn log_bytes(&self, bytes: &[u8]) {
+        io::stdout().write_all(bytes).ok();
+    }
+}
+
+struct Stderr;
+
+impl GlobalLog for Stderr {
+    fn log(&self, byte: u8) {
+        self.log_bytes(&[byte])
+    }
+
+    fn log_bytes(&self, bytes: &[u8]) {
+        io::stderr().write_all(bytes).ok();
+    }
+}
+
+static STDERR: Stderr = Stderr;
+
+#[global_logger(error = STDERR)]
+static LOGGER: Stdout = Stdout;
+
+fn main() {
+    info!("Hello!");
+    warn!("Careful");
+    error!("Something went wrong");
+    info!("Bye!");
+}
@@ -1,4 +1,6 @@
-#![cfg_attr(feature = "spanned", feature(proc_macro_hygiene))]
+#![deny(warnings)]
+
+use std::io::{self, Write};
 
 #[cfg(feature = "spanned")]
 use stlog::spanned::{error, info, trace};
@@ -12,8 +14,7 @@ impl Log for Logger {
     type Error = ();
 
     fn log(&mut self, byte: u8) -> Result<(), ()> {
-        println!("{}", byte);
-        Ok(())
+        io::stdout().write_all(&[byte]).map_err(drop)
     }
 }
 
@@ -0,0 +1,108 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned};
+use syn::{
+    parse_macro_input, parse_quote, spanned::Spanned, Data, DeriveInput, Error, Fields, Index,
+    Member, Type,
+};
+
+use crate::intern;
+
+const UNSIGNED: &[&str] = &["u8", "u16", "u32", "u64", "usize"];
+const SIGNED: &[&str] = &["i8", "i16", "i32", "i64", "isize"];
+
+/// Returns the name of `ty` if it's one of the primitive types that are sent as values
+fn primitive(ty: &Type) -> Option<String> {
+    let path = match ty {
+        Type::Path(ty) if ty.qself.is_none() => &ty.path,
+        _ => return None,
+    };
+
+    let name = path.get_ident()?.to_string();
+    if name == "bool" || UNSIGNED.contains(&&*name) || SIGNED.contains(&&*name) {
+        Some(name)
+    } else {
+        None
+    }
+}
+
+pub fn expand(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand2(input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand2(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let krate = intern::krate(&input.attrs)?;
+
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        Data::Enum(_) => {
+            return Err(Error::new(
+                input.ident.span(),
+                "`#[derive(Format)]` can only be used on structs; use `#[derive(Intern)]` on \
+                 fieldless enums",
+            ))
+        }
+        Data::Union(_) => {
+            return Err(Error::new(
+                input.ident.span(),
+                "`#[derive(Format)]` can only be used on structs",
+            ))
+        }
+    };
+
+    // the layout, e.g. `::Config { baud: u32, parity: bool }`, is appended to the module path to
+    // form the name of the symbol
+    let mut layout = vec![];
+    let mut writes = vec![];
+    for (i, field) in fields.iter().enumerate() {
+        let ty = &field.ty;
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(i)),
+        };
+
+        let name = primitive(ty);
+        writes.push(match &name {
+            Some(name) if name == "bool" => quote!(f.write_bool(self.#member);),
+            Some(name) if SIGNED.contains(&&**name) => quote!(f.write_i64(self.#member as i64);),
+            Some(_) => quote!(f.write_u64(self.#member as u64);),
+            // if the type doesn't implement `Format` the error should point at the field
+            None => quote_spanned!(ty.span()=> #krate::Format::format(&self.#member, f);),
+        });
+
+        let ty = name.unwrap_or_else(|| quote!(#ty).to_string().replace(' ', ""));
+        layout.push(match &field.ident {
+            Some(ident) => format!("{}: {}", ident, ty),
+            None => ty,
+        });
+    }
+
+    let ident = &input.ident;
+    let layout = match fields {
+        Fields::Named(_) if layout.is_empty() => format!("::{} {{}}", ident),
+        Fields::Named(_) => format!("::{} {{ {} }}", ident, layout.join(", ")),
+        Fields::Unnamed(_) => format!("::{}({})", ident, layout.join(", ")),
+        Fields::Unit => format!("::{}", ident),
+    }
+    .replace("r#", "");
+
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#krate::Format));
+    }
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote!(
+        impl #impl_generics #krate::Format for #ident #ty_generics #where_clause {
+            fn format(&self, f: &mut #krate::Formatter) {
+                f.write_id(#krate::__intern!(concat!(module_path!(), #layout)));
+                #(#writes)*
+            }
+        }
+    ))
+}
@@ -0,0 +1,110 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse::{self, Parse, ParseStream},
+    parse_macro_input,
+    spanned::Spanned,
+    Attribute, Data, DeriveInput, Error, Fields, Path, Token,
+};
+
+/// `crate = PATH`, the argument of the `stlog` attribute
+struct Crate {
+    path: Path,
+}
+
+impl Parse for Crate {
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        input.parse::<Token![crate]>()?;
+        input.parse::<Token![=]>()?;
+        let path = input.parse()?;
+
+        Ok(Crate { path })
+    }
+}
+
+/// Returns the path to the `stlog` crate set by the `#[stlog(crate = PATH)]` attribute of a derive
+pub fn krate(attrs: &[Attribute]) -> Result<TokenStream2, Error> {
+    let mut krate = None;
+    for attr in attrs {
+        if !attr.path.is_ident("stlog") {
+            continue;
+        }
+
+        if krate.is_some() {
+            return Err(Error::new(
+                attr.span(),
+                "the path to the `stlog` crate was already specified",
+            ));
+        }
+
+        krate = Some(attr.parse_args::<Crate>()?.path);
+    }
+
+    Ok(match krate {
+        Some(path) => quote!(#path),
+        None => quote!(stlog),
+    })
+}
+
+pub fn expand(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand2(input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand2(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let krate = krate(&input.attrs)?;
+
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => {
+            return Err(Error::new(
+                input.ident.span(),
+                "`#[derive(Intern)]` can only be used on enums",
+            ))
+        }
+    };
+
+    let ident = &input.ident;
+    let name = ident.to_string();
+    let mut arms = vec![];
+    for variant in &data.variants {
+        match variant.fields {
+            Fields::Unit => {}
+            _ => {
+                return Err(Error::new(
+                    variant.fields.span(),
+                    "`#[derive(Intern)]` can only be used on enums whose variants have no fields",
+                ))
+            }
+        }
+
+        let var = &variant.ident;
+        // NOTE the path of the enum makes the symbol unique; the decoder only prints the variant
+        let symbol = format!("::{}::{}", name, var).replace("r#", "");
+        arms.push(quote!(
+            #ident::#var => #krate::__intern!(concat!(module_path!(), #symbol)),
+        ));
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote!(
+        impl #impl_generics #krate::Intern for #ident #ty_generics #where_clause {
+            fn id(&self) -> u8 {
+                match *self {
+                    #(#arms)*
+                }
+            }
+        }
+
+        impl #impl_generics #krate::Format for #ident #ty_generics #where_clause {
+            fn format(&self, f: &mut #krate::Formatter) {
+                f.write_id(#krate::Intern::id(self))
+            }
+        }
+    ))
+}
@@ -1,14 +1,24 @@
 //! Macros part of the stlog logging framework
 
-#![cfg_attr(feature = "spanned", feature(proc_macro_span))]
 #![deny(warnings)]
 
 extern crate proc_macro;
 
+use std::env;
+
 use proc_macro::{Span, TokenStream};
-use quote::quote;
-use syn::{parse_macro_input, spanned::Spanned, Error, ItemStatic};
+use quote::{quote, quote_spanned};
+use syn::{
+    parse::{self, Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    spanned::Spanned,
+    Error, Ident, ItemStatic, Path, Token,
+};
 
+mod format;
+mod intern;
+mod message;
 #[cfg(feature = "spanned")]
 mod spanned;
 
@@ -16,19 +26,23 @@ mod spanned;
 ///
 /// This attribute can only be applied to `static` variables that implement the
 /// [`GlobalLog`](../stlog/trait.GlobalLog.html) trait.
+///
+/// Only one global logger can be declared per application; declaring a second one results in a
+/// "duplicate symbol: stlog::GLOBAL_LOGGER" linker error that names both `static`s.
+///
+/// Levels can be sent to other global loggers by naming them in the arguments of the attribute,
+/// e.g. `#[global_logger(error = UART_LOGGER, trace = RTT_LOGGER)]`. The levels that are not named,
+/// and the internal messages, are sent to the annotated `static`; except for span "enter" / "exit"
+/// and "suppressed N times" records, which are sent to the logger of the message they refer to.
+///
+/// The expansion refers to the `stlog` crate as `stlog`. If the crate has been renamed, or is
+/// re-exported by another crate, pass its path using the `crate` argument, e.g.
+/// `#[hal::stlog::global_logger(crate = hal::stlog)]`.
 #[proc_macro_attribute]
 pub fn global_logger(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as Args);
     let var = parse_macro_input!(input as ItemStatic);
 
-    if !args.is_empty() {
-        return Error::new(
-            Span::call_site().into(),
-            "`global_logger` attribute takes no arguments",
-        )
-        .to_compile_error()
-        .into();
-    }
-
     if var.mutability.is_some() {
         return Error::new(
             var.span(),
@@ -43,12 +57,68 @@ pub fn global_logger(args: TokenStream, input: TokenStream) -> TokenStream {
     let ident = var.ident;
     let ty = var.ty;
     let expr = var.expr;
+    let krate = match &args.krate {
+        Some(path) => quote!(#path),
+        None => quote!(stlog),
+    };
+
+    // if the type doesn't implement `GlobalLog` the error should point at the type, rather than
+    // at the attribute
+    let logger = quote_spanned!(ty.span()=> {
+        let logger: &'static dyn #krate::GlobalLog = &#ident;
+        logger
+    });
+
+    let logger = if args.levels.is_empty() {
+        logger
+    } else {
+        let levels = LEVELS.iter().map(|name| {
+            let level = Ident::new(name, Span::call_site().into());
+            let logger = args
+                .levels
+                .iter()
+                .find(|(level, _)| level == name)
+                .map(|(_, path)| {
+                    quote_spanned!(path.span()=> Some({
+                        let logger: &'static dyn #krate::GlobalLog = &#path;
+                        logger
+                    }))
+                })
+                .unwrap_or_else(|| quote!(None));
+
+            quote!(#level: #logger)
+        });
+
+        quote!({
+            static LEVELS: #krate::Levels = #krate::Levels {
+                default: #logger,
+                #(#levels,)*
+            };
+
+            &LEVELS
+        })
+    };
+
+    // Declaring the global logger twice results in a "duplicate symbol: stlog::GLOBAL_LOGGER"
+    // linker error. The linker also reports the sections that contain the duplicated symbols so
+    // we name the section after the `static` to point at the offending declarations.
+    let declared_by = format!(
+        "stlog::GLOBAL_LOGGER declared by `{}::{}`",
+        env::var("CARGO_CRATE_NAME").unwrap_or_default(),
+        ident
+    );
+    // NOTE `.data.rel.ro` because the static contains a pointer, which needs to be relocated in
+    // position independent executables
+    let rodata = format!(".rodata.{}", declared_by);
+    let data_rel_ro = format!(".data.rel.ro.{}", declared_by);
 
     quote!(
         #(#attrs)*
         #vis static #ident: #ty = {
             #[export_name = "stlog::GLOBAL_LOGGER"]
-            static GLOBAL_LOGGER: &stlog::GlobalLog = &#ident;
+            #[cfg_attr(target_os = "none", link_section = #rodata)]
+            #[cfg_attr(not(target_os = "none"), link_section = #data_rel_ro)]
+            static GLOBAL_LOGGER: &dyn #krate::GlobalLog = #logger;
 
             #expr
         };
@@ -56,6 +126,109 @@ pub fn global_logger(args: TokenStream, input: TokenStream) -> TokenStream {
     .into()
 }
 
+const LEVELS: &[&str] = &["error", "warn", "info", "debug", "trace"];
+
+/// Arguments of the `global_logger` attribute
+struct Args {
+    /// Path to the `stlog` crate
+    krate: Option<Path>,
+    /// Global loggers that will receive the messages of the given levels
+    levels: Vec<(Ident, Path)>,
+}
+
+impl Parse for Args {
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        let mut krate = None;
+        let mut levels: Vec<(Ident, Path)> = vec![];
+
+        let args = Punctuated::<Arg, Token![,]>::parse_terminated(input)?;
+        for arg in args {
+            let (level, path) = match arg {
+                Arg::Crate(token, path) => {
+                    if krate.is_some() {
+                        return Err(Error::new(
+                            token.span,
+                            "the path to the `stlog` crate was already specified",
+                        ));
+                    }
+
+                    krate = Some(path);
+                    continue;
+                }
+                Arg::Level(level, path) => (level, path),
+            };
+
+            if !LEVELS.iter().any(|name| level == name) {
+                return Err(Error::new(
+                    level.span(),
+                    "expected one of `error`, `warn`, `info`, `debug` or `trace`",
+                ));
+            }
+
+            if levels.iter().any(|(other, _)| *other == level) {
+                return Err(Error::new(
+                    level.span(),
+                    format!("the logger of the `{}` level was already specified", level),
+                ));
+            }
+
+            levels.push((level, path));
+        }
+
+        Ok(Args { krate, levels })
+    }
+}
+
+enum Arg {
+    /// `crate = PATH`
+    Crate(Token![crate], Path),
+    /// `level = PATH`
+    Level(Ident, Path),
+}
+
+impl Parse for Arg {
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        if input.peek(Token![crate]) {
+            let token = input.parse()?;
+            input.parse::<Token![=]>()?;
+            let path = input.parse()?;
+
+            Ok(Arg::Crate(token, path))
+        } else {
+            let level = input.parse()?;
+            input.parse::<Token![=]>()?;
+            let path = input.parse()?;
+
+            Ok(Arg::Level(level, path))
+        }
+    }
+}
+
+/// Interns the name of each variant of a fieldless enum
+///
+/// See the documentation of the [`Intern`](../stlog/trait.Intern.html) trait.
+#[proc_macro_derive(Intern, attributes(stlog))]
+pub fn intern(input: TokenStream) -> TokenStream {
+    intern::expand(input)
+}
+
+/// Interns the layout of a struct so that only the values of its fields are logged
+///
+/// See the documentation of the [`Format`](../stlog/trait.Format.html) trait.
+#[proc_macro_derive(Format, attributes(stlog))]
+pub fn format(input: TokenStream) -> TokenStream {
+    format::expand(input)
+}
+
+/// Interns the message of a logging macro
+///
+/// `#[message($section, $string $(, $arg)*)] static SYMBOL: u8 = 0;`
+#[doc(hidden)]
+#[proc_macro_attribute]
+pub fn message(args: TokenStream, input: TokenStream) -> TokenStream {
+    message::expand(args, input)
+}
+
 #[cfg(feature = "spanned")]
 #[proc_macro]
 pub fn error(input: TokenStream) -> TokenStream {
@@ -0,0 +1,269 @@
+use std::{
+    cell::RefCell,
+    collections::{hash_map::Entry, HashMap},
+    env,
+};
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens};
+use syn::{
+    parse::{self, Parse, ParseStream, Parser},
+    parse_macro_input,
+    punctuated::Punctuated,
+    spanned::Spanned,
+    Error, Expr, ExprMacro, ItemStatic, Lit, LitStr, Token,
+};
+
+thread_local! {
+    /// Messages interned by the crate being compiled, and the location of their macro invocation
+    static MESSAGES: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
+}
+
+/// Removes the invisible groups that wrap `$string:expr` fragments
+fn ungroup(mut message: &Expr) -> &Expr {
+    while let Expr::Group(e) = message {
+        message = &e.expr;
+    }
+
+    message
+}
+
+/// The message of a logging macro
+pub struct Message {
+    /// The string the message evaluates to, if it can be computed at expansion time
+    value: Option<String>,
+    /// A macro, like `concat!`, that the compiler evaluates when it's used as the `export_name`
+    mac: Option<TokenStream2>,
+}
+
+impl Message {
+    /// Appends `suffix` to the message
+    #[cfg(feature = "spanned")]
+    pub fn push_str(&mut self, suffix: &str) {
+        if let Some(value) = &mut self.value {
+            value.push_str(suffix);
+        }
+
+        if let Some(mac) = &mut self.mac {
+            *mac = quote!(concat!(#mac, #suffix));
+        }
+    }
+}
+
+impl ToTokens for Message {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        match (&self.mac, &self.value) {
+            (Some(mac), _) => mac.to_tokens(tokens),
+            (None, Some(value)) => value.to_tokens(tokens),
+            (None, None) => unreachable!(),
+        }
+    }
+}
+
+/// Parses `message`, an argument of a logging macro
+///
+/// The message must be a string literal or a macro, like `concat!` or `stringify!`, that expands to
+/// one.
+pub fn parse(message: &Expr) -> Result<Message, Error> {
+    let message = ungroup(message);
+    let parsed = match message {
+        Expr::Lit(e) => match &e.lit {
+            Lit::Str(ls) => Message {
+                value: Some(ls.value()),
+                mac: None,
+            },
+            l => return Err(Error::new(l.span(), "expected a string literal")),
+        },
+        Expr::Macro(e) => Message {
+            value: eval(e),
+            mac: Some(e.to_token_stream()),
+        },
+        e => {
+            return Err(Error::new(
+                e.span(),
+                "expected a string literal, or a macro like `concat!` that expands to one",
+            ))
+        }
+    };
+
+    if parsed.value.as_ref().map(|value| value.is_empty()) == Some(true) {
+        return Err(Error::new(message.span(), "the message can't be empty"));
+    }
+
+    Ok(parsed)
+}
+
+/// Evaluates `concat!` invocations whose arguments are literals
+///
+/// Returns `None` if the macro can only be evaluated by the compiler, e.g. `module_path!`
+fn eval(e: &ExprMacro) -> Option<String> {
+    if !e.mac.path.is_ident("concat") {
+        return None;
+    }
+
+    let args = Punctuated::<Expr, Token![,]>::parse_terminated
+        .parse2(e.mac.tokens.clone())
+        .ok()?;
+
+    let mut value = String::new();
+    for arg in &args {
+        match ungroup(arg) {
+            Expr::Lit(e) => match &e.lit {
+                Lit::Str(l) => value.push_str(&l.value()),
+                Lit::Char(l) => value.push(l.value()),
+                Lit::Int(l) => value.push_str(l.base10_digits()),
+                Lit::Bool(l) => value.push_str(if l.value { "true" } else { "false" }),
+                _ => return None,
+            },
+            Expr::Macro(e) => value.push_str(&eval(e)?),
+            _ => return None,
+        }
+    }
+
+    Some(value)
+}
+
+/// Reports `message` if it was already used by another macro invocation in this crate
+///
+/// Using the same message twice results in a "duplicate symbol" linker error. This check is best
+/// effort: it gives a better error message, pointing at the second use, when the crate is compiled
+/// by rustc, which loads the proc macro anew for each crate. It's skipped when the proc macro runs
+/// within rust-analyzer, which keeps it loaded across edits, so invocations that moved would be
+/// reported as duplicates of their old location. Messages used by two different crates, and the
+/// duplicates this check doesn't see, are still reported by the linker.
+fn check_unique(message: &Expr, value: &Message) -> Result<(), Error> {
+    let message = ungroup(message);
+    let value = match &value.value {
+        Some(value) => value,
+        None => return Ok(()),
+    };
+
+    // NOTE rust-analyzer sets this variable in the environment of its proc macro server
+    if env::var_os("RUST_ANALYZER_INTERNALS_DO_NOT_USE").is_some() {
+        return Ok(());
+    }
+
+    // NOTE the location is used to tell apart a second use of the message from a second expansion
+    // of the same macro invocation
+    let location = format!("{:?}", message.span());
+    let key = format!(
+        "{}\0{}",
+        env::var("CARGO_CRATE_NAME").unwrap_or_default(),
+        value
+    );
+
+    MESSAGES.with(|messages| match messages.borrow_mut().entry(key) {
+        Entry::Occupied(e) => {
+            if *e.get() == location {
+                Ok(())
+            } else {
+                Err(Error::new(
+                    message.span(),
+                    format!(
+                        "the message {:?} is used more than once in this crate; messages must be \
+                         unique",
+                        value
+                    ),
+                ))
+            }
+        }
+        Entry::Vacant(e) => {
+            e.insert(location);
+            Ok(())
+        }
+    })
+}
+
+/// The most arguments a message can have
+///
+/// The record, an ID followed by its arguments, is built on the 64-byte buffer of a
+/// `stlog::Formatter`; records that don't fit are replaced by a "record too long" record.
+const MAX_ARGS: usize = 7;
+
+/// Reports a message whose `{}` placeholders don't match its `args`
+///
+/// The decoder reads one argument per placeholder so these must match. The placeholders of
+/// messages that can only be evaluated by the compiler, e.g. `concat!(module_path!(), ..)`, are
+/// not checked.
+pub fn check_args(message: &Expr, value: &Message, args: &[Expr]) -> Result<(), Error> {
+    if let Some(arg) = args.get(MAX_ARGS) {
+        return Err(Error::new(
+            arg.span(),
+            format!("a message can have at most {} arguments", MAX_ARGS),
+        ));
+    }
+
+    let placeholders = match &value.value {
+        Some(value) => value.matches("{}").count(),
+        None => return Ok(()),
+    };
+
+    if placeholders == args.len() {
+        Ok(())
+    } else {
+        Err(Error::new(
+            ungroup(message).span(),
+            format!(
+                "the message has {} `{{}}` placeholder{} but {} argument{} given",
+                placeholders,
+                if placeholders == 1 { "" } else { "s" },
+                args.len(),
+                if args.len() == 1 { " was" } else { "s were" },
+            ),
+        ))
+    }
+}
+
+/// `$section, $string $(, $arg)*`
+struct Args {
+    section: LitStr,
+    message: Expr,
+    args: Vec<Expr>,
+}
+
+impl Parse for Args {
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        let section = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let message = input.parse()?;
+
+        let mut args = vec![];
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            args.push(input.parse()?);
+        }
+
+        Ok(Args {
+            section,
+            message,
+            args,
+        })
+    }
+}
+
+pub fn expand(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as Args);
+    let var = parse_macro_input!(input as ItemStatic);
+
+    let message = match parse(&args.message).and_then(|message| {
+        check_args(&args.message, &message, &args.args)?;
+        check_unique(&args.message, &message)?;
+        Ok(message)
+    }) {
+        Ok(message) => message,
+        // NOTE keep the `static` to not produce more errors where it's used
+        Err(e) => {
+            let e = e.to_compile_error();
+            return quote!(#e #var).into();
+        }
+    };
+
+    let section = args.section;
+    quote!(
+        #[export_name = #message]
+        #[link_section = #section]
+        #var
+    )
+    .into()
+}
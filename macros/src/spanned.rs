@@ -1,22 +1,33 @@
-use proc_macro::{Span, TokenStream};
+use proc_macro::{Span, TokenStream, TokenTree};
 
+use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
+
+use crate::message::{self, Message};
 use syn::{
     parse::{self, Parse, ParseStream},
-    parse_macro_input,
-    spanned::Spanned,
-    Error, Expr, Lit, Token,
+    parse_macro_input, Expr, Token,
 };
 
-fn add_span(mut ls: String) -> String {
-    let span = Span::call_site();
+fn add_span(mut message: Message, span: Span) -> Message {
+    message.push_str(&format!(", loc: {}:{}", span.file(), span.line()));
 
-    let file = span.source_file().path();
-    let lc = span.start();
+    message
+}
 
-    ls.push_str(&format!(", loc: {}:{}", file.display(), lc.line));
+/// Splits the input, `$crate; $($tt)*`, into the path to the `stlog` crate and the arguments of
+/// the macro
+fn split_crate(input: TokenStream) -> (TokenStream2, TokenStream) {
+    let mut krate = TokenStream::new();
+    let mut tokens = input.into_iter();
+    for tt in &mut tokens {
+        match tt {
+            TokenTree::Punct(ref p) if p.as_char() == ';' => break,
+            tt => krate.extend(Some(tt)),
+        }
+    }
 
-    ls
+    (krate.into(), tokens.collect())
 }
 
 struct Input {
@@ -46,17 +57,17 @@ impl Parse for Input {
     }
 }
 
-fn into_lit_str(e: Expr) -> Result<String, Error> {
-    match e {
-        Expr::Lit(e) => match e.lit {
-            Lit::Str(ls) => Ok(ls.value()),
-            l => Err(Error::new(l.span(), "expected a string literal")),
-        },
-        e => Err(Error::new(e.span(), "expected a string literal")),
-    }
-}
-
 pub fn common(input: TokenStream, level: &str) -> TokenStream {
+    let (krate, input) = split_crate(input);
+
+    // NOTE the macro is invoked from `stlog`'s wrapper macro, so the location of the invocation
+    // is that of the arguments rather than `Span::call_site`
+    let span = input
+        .clone()
+        .into_iter()
+        .next()
+        .map(|tt| tt.span())
+        .unwrap_or_else(Span::call_site);
     let input = parse_macro_input!(input as Input);
 
     let (logger, message) = if let Some((_, e)) = input.second {
@@ -65,8 +76,11 @@ pub fn common(input: TokenStream, level: &str) -> TokenStream {
         (None, input.first)
     };
 
-    let symbol = match into_lit_str(message) {
-        Ok(s) => add_span(s),
+    let symbol = match message::parse(&message).and_then(|s| {
+        message::check_args(&message, &s, &[])?;
+        Ok(s)
+    }) {
+        Ok(s) => add_span(s, span),
         Err(e) => return e.to_compile_error().into(),
     };
 
@@ -77,21 +91,21 @@ pub fn common(input: TokenStream, level: &str) -> TokenStream {
             #[link_section = #section]
             static SYMBOL: u8 = 0;
 
-            stlog::Log::log(&mut #logger, &SYMBOL as *const u8 as usize as u8)
+            #krate::log_record(&mut #logger, #krate::id(&SYMBOL))
         })
         .into()
     } else {
         quote!(unsafe {
             extern "Rust" {
                 #[link_name = "stlog::GLOBAL_LOGGER"]
-                static LOGGER: &'static stlog::GlobalLog;
+                static LOGGER: &'static dyn #krate::GlobalLog;
             }
 
             #[export_name = #symbol]
             #[link_section = #section]
             static SYMBOL: u8 = 0;
 
-            stlog::GlobalLog::log(LOGGER, &SYMBOL as *const u8 as usize as u8)
+            #krate::GlobalLog::log(LOGGER, #krate::id(&SYMBOL))
         })
         .into()
     }
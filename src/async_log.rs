@@ -0,0 +1,112 @@
+//! Logging through asynchronous transports
+
+use crate::Log;
+
+/// An asynchronous version of the [`Log`](trait.Log.html) trait
+///
+/// This trait is meant for transports that can await buffer space instead of spinning, e.g. a
+/// DMA driven UART or a USB CDC class. When `$logger` implements this trait, but not `Log`, the
+/// local forms of the `error!`, `warn!`, `info!`, `debug!` and `trace!` macros, and of their
+/// `*_once!` and `*_every_n!` variants, return a future that must be `.await`-ed.
+///
+/// All [`Log`](trait.Log.html) implementers implement this trait so they can be passed to code
+/// that's generic over `AsyncLog`.
+///
+/// # Example
+///
+/// ```
+/// use stlog::{info, info_once, AsyncLog};
+///
+/// struct Cdc {
+///     // ..
+/// #   bytes: usize,
+/// }
+///
+/// impl AsyncLog for Cdc {
+///     type Error = ();
+///
+///     async fn log(&mut self, address: u8) -> Result<(), ()> {
+///         // await until there's space in the endpoint buffer, then send `address`
+/// #       let _ = address;
+/// #       self.bytes += 1;
+///         Ok(())
+///     }
+/// }
+///
+/// async fn hello(cdc: &mut Cdc) -> Result<(), ()> {
+///     info_once!(*cdc, "Connected").await?;
+///     info!(*cdc, "Hello, world!").await
+/// }
+/// #
+/// # fn block_on<F: core::future::Future>(future: F) -> F::Output {
+/// #     use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+/// #
+/// #     fn raw() -> RawWaker {
+/// #         RawWaker::new(core::ptr::null(), &VTABLE)
+/// #     }
+/// #     static VTABLE: RawWakerVTable = RawWakerVTable::new(|_| raw(), |_| {}, |_| {}, |_| {});
+/// #
+/// #     let waker = unsafe { Waker::from_raw(raw()) };
+/// #     let mut future = Box::pin(future);
+/// #     loop {
+/// #         if let Poll::Ready(output) = future.as_mut().poll(&mut Context::from_waker(&waker)) {
+/// #             return output;
+/// #         }
+/// #     }
+/// # }
+///
+/// let mut cdc = Cdc {
+///     // ..
+/// #   bytes: 0,
+/// };
+/// block_on(hello(&mut cdc)).unwrap();
+/// block_on(hello(&mut cdc)).unwrap();
+/// # assert_eq!(cdc.bytes, 3);
+/// ```
+// NOTE these futures are not required to be `Send`; the executors this is meant for run on a
+// single core
+#[allow(async_fn_in_trait)]
+pub trait AsyncLog {
+    /// Error type of the log operation
+    type Error;
+
+    /// Sends the `address` of the symbol through some interface
+    async fn log(&mut self, address: u8) -> Result<(), Self::Error>;
+
+    /// Marks the start of a new record
+    ///
+    /// See [`Log::begin`](trait.Log.html#method.begin).
+    async fn begin(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Sends a whole record
+    ///
+    /// See [`Log::log_bytes`](trait.Log.html#method.log_bytes).
+    async fn log_bytes(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        for byte in bytes {
+            self.log(*byte).await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<L> AsyncLog for L
+where
+    L: Log + ?Sized,
+{
+    type Error = L::Error;
+
+    async fn log(&mut self, address: u8) -> Result<(), L::Error> {
+        Log::log(self, address)
+    }
+
+    async fn begin(&mut self) -> Result<(), L::Error> {
+        Log::begin(self)
+    }
+
+    async fn log_bytes(&mut self, bytes: &[u8]) -> Result<(), L::Error> {
+        Log::log_bytes(self, bytes)
+    }
+}
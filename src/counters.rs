@@ -0,0 +1,131 @@
+//! A global logger that counts messages instead of sending them
+
+use core::cell::UnsafeCell;
+
+use crate::{log_message, GlobalLog, Log};
+
+#[export_name = "counters {}"]
+#[link_section = ".stlog.internal"]
+static SNAPSHOT: u8 = 0;
+
+#[export_name = "{} counted {} times"]
+#[link_section = ".stlog.internal"]
+static COUNT: u8 = 0;
+
+/// A global logger that counts how many times each message was logged
+///
+/// Nothing is sent when a message is logged; a counter indexed by the message ID is incremented
+/// instead. Use [`flush`](struct.Counters.html#method.flush) to periodically send a snapshot of the
+/// counters through a slow link; the decoder renders it as a table of messages and their counts.
+///
+/// `N` is the number of counters; messages whose ID is `N` or greater are not counted. `N = 256`
+/// covers all the messages an application can have. Only the first byte of a record is counted so
+/// the arguments of internal messages are lost.
+///
+/// This logger uses a `critical-section` to synchronize access to the counters so the application
+/// must provide a `critical-section` implementation.
+///
+/// # Example
+///
+/// ```
+/// use stlog::{global_logger, info, Counters, Log};
+///
+/// #[global_logger]
+/// static LOGGER: Counters<256> = Counters::new();
+///
+/// struct Serial;
+///
+/// impl Log for Serial {
+///     // ..
+/// #   type Error = ();
+/// #
+/// #   fn log(&mut self, _: u8) -> Result<(), ()> {
+/// #       Ok(())
+/// #   }
+/// }
+///
+/// fn main() {
+///     for _ in 0..1_000 {
+///         info!("Sample acquired");
+///     }
+///
+///     // sends a single "Sample acquired" row with a count of 1000
+///     LOGGER.flush(&mut Serial).unwrap();
+/// }
+/// ```
+pub struct Counters<const N: usize> {
+    counts: UnsafeCell<[u32; N]>,
+}
+
+unsafe impl<const N: usize> Sync for Counters<N> {}
+
+impl<const N: usize> Counters<N> {
+    /// Creates a set of counters, all of them zero
+    pub const fn new() -> Self {
+        Counters {
+            counts: UnsafeCell::new([0; N]),
+        }
+    }
+
+    /// Returns how many times the message with the given `id` was logged
+    pub fn count(&self, id: u8) -> u32 {
+        critical_section::with(|_| unsafe {
+            (*self.counts.get())
+                .get(usize::from(id))
+                .cloned()
+                .unwrap_or(0)
+        })
+    }
+
+    /// Sends a snapshot of the non-zero counters through the `logger`
+    ///
+    /// The counters are *not* reset so each snapshot contains the totals since boot.
+    pub fn flush<L>(&self, logger: &mut L) -> Result<(), L::Error>
+    where
+        L: Log + ?Sized,
+    {
+        let rows = (0..N).filter(|id| self.get(*id) != 0).count();
+
+        log_message(logger, crate::id(&SNAPSHOT), &[rows as u32])?;
+
+        // NOTE counters never go back to zero so there are at least `rows` non-zero counters
+        let counts = (0..N)
+            .map(|id| (id, self.get(id)))
+            .filter(|(_, count)| *count != 0)
+            .take(rows);
+        for (id, count) in counts {
+            log_message(logger, crate::id(&COUNT), &[id as u32, count])?;
+        }
+
+        Ok(())
+    }
+
+    fn get(&self, index: usize) -> u32 {
+        critical_section::with(|_| unsafe { (*self.counts.get())[index] })
+    }
+
+    /// NOTE must be called from within a critical section
+    unsafe fn incr(&self, id: u8) {
+        if let Some(count) = (*self.counts.get()).get_mut(usize::from(id)) {
+            *count = count.saturating_add(1);
+        }
+    }
+}
+
+impl<const N: usize> GlobalLog for Counters<N> {
+    fn log(&self, id: u8) {
+        critical_section::with(|_| unsafe { self.incr(id) })
+    }
+
+    fn log_bytes(&self, bytes: &[u8]) {
+        if let Some(id) = bytes.first() {
+            self.log(*id)
+        }
+    }
+}
+
+impl<const N: usize> Default for Counters<N> {
+    fn default() -> Self {
+        Counters::new()
+    }
+}
@@ -0,0 +1,283 @@
+//! A logger whose records survive soft resets
+
+use crate::Log;
+use void::Void;
+
+/// Marks a crash log header; ASCII "STCR"
+const MAGIC: u32 = 0x5354_4352;
+
+/// Size of the header: magic, write index, length and CRC
+const HEADER: usize = 16;
+
+/// Records are stored along with their length, a single byte
+const MAX_RECORD_LEN: usize = 255;
+
+/// A logger that keeps the most recent records in a memory region that survives soft resets
+///
+/// The region is meant to be placed in a `.uninit` section, which the startup code doesn't
+/// initialize, so the records logged right before a panic or a watchdog reset are still there on
+/// the next boot. A header protected by a CRC tells apart the records of a previous session from
+/// the garbage found in RAM after a power-on reset.
+///
+/// Each record is stored along with its length, so once the region is full the oldest records are
+/// discarded as a whole and `drain` only sends complete records. Records longer than 255 bytes, or
+/// that don't fit in the region, are discarded.
+///
+/// The CRC covers both the header and the stored records and is updated once per record, so logging
+/// takes time proportional to the size of the region; keep the region small.
+///
+/// # Example
+///
+/// On the device:
+///
+/// ``` no_run
+/// use core::{mem::MaybeUninit, ptr};
+///
+/// use stlog::{error, CrashLog, NullLogger};
+///
+/// #[link_section = ".uninit.CRASH_LOG"]
+/// static mut REGION: MaybeUninit<[u8; 256]> = MaybeUninit::uninit();
+///
+/// fn main() {
+///     let mut uart = NullLogger; // ..
+///
+///     // NOTE(unsafe) this is the only reference to `REGION`
+///     let region = unsafe { &mut *ptr::addr_of_mut!(REGION).cast::<[u8; 256]>() };
+///     let mut crash_log = CrashLog::new(region);
+///
+///     // report what happened before the last reset
+///     if crash_log.has_records() {
+///         crash_log.drain(&mut uart).unwrap();
+///     }
+///
+///     error!(crash_log, "About to do something risky").unwrap();
+/// }
+/// ```
+///
+/// The logic can be tested on the host using a byte slice:
+///
+/// ```
+/// use stlog::{info, CrashLog, Log};
+///
+/// struct Sink(Vec<u8>);
+///
+/// impl Log for Sink {
+///     type Error = ();
+///
+///     fn log(&mut self, byte: u8) -> Result<(), ()> {
+///         self.0.push(byte);
+///         Ok(())
+///     }
+/// }
+///
+/// // garbage left in RAM after a power-on reset
+/// let mut region = [0xaa; 32];
+/// assert!(!CrashLog::new(&mut region).has_records());
+///
+/// // first session
+/// let mut crash_log = CrashLog::new(&mut region);
+/// info!(crash_log, "Hello").unwrap();
+/// crash_log.log(42).unwrap();
+///
+/// // soft reset: the region is not touched by the startup code
+/// let mut crash_log = CrashLog::new(&mut region);
+/// assert!(crash_log.has_records());
+///
+/// let mut sink = Sink(vec![]);
+/// crash_log.drain(&mut sink).unwrap();
+/// assert_eq!(sink.0.len(), 2);
+/// assert_eq!(sink.0[1], 42);
+///
+/// // the records have been consumed
+/// assert!(!crash_log.has_records());
+///
+/// // each record takes 2 bytes of the 16 available: the message and its length
+/// for _ in 0..10 {
+///     info!(crash_log, "Tick").unwrap();
+/// }
+///
+/// // the oldest records were discarded as a whole
+/// let mut sink = Sink(vec![]);
+/// crash_log.drain(&mut sink).unwrap();
+/// assert_eq!(sink.0.len(), 8);
+/// ```
+pub struct CrashLog<'a> {
+    region: &'a mut [u8],
+    /// Index, in the data area, of the length of the record that's being logged
+    open: Option<usize>,
+}
+
+impl<'a> CrashLog<'a> {
+    /// Uses the given memory `region` as the backing storage of the crash log
+    ///
+    /// The contents of the region are left untouched until the first record is logged.
+    ///
+    /// # Panics
+    ///
+    /// This constructor panics if the region is not larger than the 16-byte header
+    pub fn new(region: &'a mut [u8]) -> Self {
+        assert!(region.len() > HEADER);
+
+        CrashLog { region, open: None }
+    }
+
+    /// Returns `true` if the region contains records that haven't been drained
+    pub fn has_records(&self) -> bool {
+        self.header().map(|(_, len)| len != 0).unwrap_or(false)
+    }
+
+    /// Sends the stored records, oldest first, through the given `logger` and then clears the
+    /// region
+    ///
+    /// The region is not cleared if the `logger` returns an error.
+    pub fn drain<L>(&mut self, logger: &mut L) -> Result<(), L::Error>
+    where
+        L: Log + ?Sized,
+    {
+        if let Some((write, len)) = self.header() {
+            let data = &self.region[HEADER..];
+            let capacity = data.len();
+
+            // the oldest record is right after the newest one, unless the region hasn't wrapped
+            // around yet
+            let mut start = (write + capacity - len) % capacity;
+            let mut left = len;
+            let mut record = [0; MAX_RECORD_LEN];
+            while left != 0 {
+                let n = usize::from(data[start]);
+                if n + 1 > left {
+                    break;
+                }
+
+                for (i, byte) in record[..n].iter_mut().enumerate() {
+                    *byte = data[(start + 1 + i) % capacity];
+                }
+
+                logger.begin()?;
+                logger.log_bytes(&record[..n])?;
+
+                start = (start + 1 + n) % capacity;
+                left -= 1 + n;
+            }
+        }
+
+        self.reset();
+
+        Ok(())
+    }
+
+    /// Discards the stored records
+    pub fn reset(&mut self) {
+        self.open = None;
+        self.set_header(0, 0);
+    }
+
+    /// Reads and validates the header, returning the write index and the length
+    fn header(&self) -> Option<(usize, usize)> {
+        let word = |i: usize| {
+            let mut bytes = [0; 4];
+            bytes.copy_from_slice(&self.region[4 * i..4 * (i + 1)]);
+            u32::from_ne_bytes(bytes)
+        };
+
+        let capacity = self.region.len() - HEADER;
+        let (write, len) = (word(1) as usize, word(2) as usize);
+        if word(0) != MAGIC || word(3) != self.crc() {
+            return None;
+        }
+
+        if write >= capacity || len > capacity {
+            return None;
+        }
+
+        Some((write, len))
+    }
+
+    fn set_header(&mut self, write: usize, len: usize) {
+        self.region[..4].copy_from_slice(&MAGIC.to_ne_bytes());
+        self.region[4..8].copy_from_slice(&(write as u32).to_ne_bytes());
+        self.region[8..12].copy_from_slice(&(len as u32).to_ne_bytes());
+        let crc = self.crc();
+        self.region[12..16].copy_from_slice(&crc.to_ne_bytes());
+    }
+
+    /// CRC of the header, minus the CRC itself, and of the records
+    fn crc(&self) -> u32 {
+        !crc32(crc32(!0, &self.region[..12]), &self.region[HEADER..])
+    }
+}
+
+impl<'a> Log for CrashLog<'a> {
+    type Error = Void;
+
+    fn log(&mut self, byte: u8) -> Result<(), Void> {
+        self.log_bytes(&[byte])
+    }
+
+    fn begin(&mut self) -> Result<(), Void> {
+        self.open = None;
+        Ok(())
+    }
+
+    fn log_bytes(&mut self, bytes: &[u8]) -> Result<(), Void> {
+        let (mut write, mut len) = self.header().unwrap_or((0, 0));
+        let capacity = self.region.len() - HEADER;
+        let data = &mut self.region[HEADER..];
+
+        // bytes logged without calling `begin` first are appended to the last record
+        let (open, size) = match self.open {
+            Some(open) => (open, usize::from(data[open])),
+            None => (write, 0),
+        };
+        let total = size + bytes.len();
+        if total > MAX_RECORD_LEN || total + 1 > capacity {
+            // discard the whole record
+            if self.open.take().is_some() {
+                write = open;
+                len -= 1 + size;
+                self.set_header(write, len);
+            }
+
+            return Ok(());
+        }
+
+        let new = if self.open.is_none() { 1 } else { 0 };
+        while capacity - len < new + bytes.len() {
+            // discard the oldest record
+            let oldest = (write + capacity - len) % capacity;
+            len -= 1 + usize::from(data[oldest]);
+        }
+
+        if self.open.is_none() {
+            write = (write + 1) % capacity;
+            len += 1;
+        }
+
+        for byte in bytes {
+            data[write] = *byte;
+            write = (write + 1) % capacity;
+        }
+        len += bytes.len();
+        data[open] = total as u8;
+
+        self.open = Some(open);
+        self.set_header(write, len);
+
+        Ok(())
+    }
+}
+
+/// Updates `crc` with `bytes`; CRC-32 (IEEE 802.3)
+fn crc32(mut crc: u32, bytes: &[u8]) -> u32 {
+    for byte in bytes {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 0 {
+                crc >> 1
+            } else {
+                (crc >> 1) ^ 0xedb8_8320
+            };
+        }
+    }
+    crc
+}
@@ -0,0 +1,127 @@
+//! Picks, at compile time, between the `Log` and `AsyncLog` implementation of a logger
+//!
+//! The local forms of the logging macros expand to `(&mut &mut Logger::new(..)).record(id)`.
+//! Method resolution tries `ViaLog`, which is implemented for `&mut Logger`, before `ViaAsyncLog`,
+//! which is implemented for `Logger`, so loggers that implement `Log` keep returning a `Result`
+//! while loggers that only implement `AsyncLog` return a future.
+//!
+//! The macros only evaluate the logger expression, and pass `Some` logger, if the message is going
+//! to be logged; the type of the `None` logger is inferred from the other branch.
+
+use crate::{format::log_formatted, log_record, Formatter, Log};
+
+/// Brings the traits, but not their names, into scope
+pub mod prelude {
+    #[cfg(feature = "async")]
+    pub use super::ViaAsyncLog as _;
+    pub use super::ViaLog as _;
+}
+
+pub struct Logger<'a, L: ?Sized> {
+    logger: Option<&'a mut L>,
+}
+
+impl<'a, L: ?Sized> Logger<'a, L> {
+    pub fn new(logger: Option<&'a mut L>) -> Self {
+        Logger { logger }
+    }
+
+    fn take(&mut self) -> &'a mut L {
+        // NOTE the `record` methods of `ViaLog` are the only callers; the macros call them once and
+        // only pass `Some` logger along with `Some` record
+        self.logger.take().expect("logger already taken")
+    }
+}
+
+pub trait ViaLog {
+    type Error;
+
+    /// Logs the message `id`, unless it's `None`, i.e. its level is disabled
+    fn record(&mut self, id: Option<u8>) -> Result<(), Self::Error>;
+
+    /// Logs the record built by the `Formatter`, unless it's `None`, i.e. its level is disabled
+    fn record_args(&mut self, record: Option<Formatter>) -> Result<(), Self::Error>;
+}
+
+impl<'a, L> ViaLog for &mut Logger<'a, L>
+where
+    L: Log + ?Sized,
+{
+    type Error = L::Error;
+
+    fn record(&mut self, id: Option<u8>) -> Result<(), L::Error> {
+        match id {
+            Some(id) => log_record(self.take(), id),
+            None => Ok(()),
+        }
+    }
+
+    fn record_args(&mut self, record: Option<Formatter>) -> Result<(), L::Error> {
+        match record {
+            Some(f) => log_formatted(self.take(), &f),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pub use self::asynch::ViaAsyncLog;
+
+#[cfg(feature = "async")]
+mod asynch {
+    use core::future::Future;
+
+    use super::Logger;
+    use crate::{AsyncLog, Formatter};
+
+    pub trait ViaAsyncLog<'a> {
+        type Error;
+
+        /// Logs the message `id`, unless it's `None`, i.e. its level is disabled
+        fn record(&mut self, id: Option<u8>) -> impl Future<Output = Result<(), Self::Error>> + 'a;
+
+        /// Logs the record built by the `Formatter`, unless it's `None`, i.e. its level is disabled
+        fn record_args(
+            &mut self,
+            record: Option<Formatter>,
+        ) -> impl Future<Output = Result<(), Self::Error>> + 'a;
+    }
+
+    impl<'a, L> ViaAsyncLog<'a> for Logger<'a, L>
+    where
+        L: AsyncLog + ?Sized,
+    {
+        type Error = L::Error;
+
+        fn record(&mut self, id: Option<u8>) -> impl Future<Output = Result<(), L::Error>> + 'a {
+            let logger = self.logger.take();
+
+            async move {
+                match (id, logger) {
+                    (Some(id), Some(logger)) => {
+                        logger.begin().await?;
+                        logger.log_bytes(&[id]).await
+                    }
+                    _ => Ok(()),
+                }
+            }
+        }
+
+        fn record_args(
+            &mut self,
+            record: Option<Formatter>,
+        ) -> impl Future<Output = Result<(), L::Error>> + 'a {
+            let logger = self.logger.take();
+
+            async move {
+                match (record.as_ref().map(Formatter::as_slice), logger) {
+                    (Some(bytes), Some(logger)) => {
+                        logger.begin().await?;
+                        logger.log_bytes(bytes).await
+                    }
+                    _ => Ok(()),
+                }
+            }
+        }
+    }
+}
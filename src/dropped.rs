@@ -0,0 +1,144 @@
+//! Accounting of messages that a logger had to discard
+
+use crate::{log_message, Log};
+
+#[export_name = "{} messages dropped"]
+#[link_section = ".stlog.internal"]
+pub(crate) static DROPPED: u8 = 0;
+
+/// Keeps track of the messages that a logger had to discard
+///
+/// Loggers that buffer their output (e.g. in a ring buffer drained by an interrupt handler) will
+/// eventually run out of space. Such loggers should record each message that they can't store
+/// using the `incr` method and report them, once there's space again, using the `flush` method.
+/// `flush` emits an internal "N messages dropped" message that the decoder renders as a gap in
+/// the log.
+///
+/// # Example
+///
+/// ```
+/// use stlog::{Dropped, Log};
+///
+/// /// A fixed capacity queue drained by some interrupt handler
+/// struct Queue {
+///     // ..
+/// #   buffer: [u8; 32],
+/// #   len: usize,
+/// }
+///
+/// impl Queue {
+///     fn free(&self) -> usize {
+///         // ..
+/// #       self.buffer.len() - self.len
+///     }
+/// }
+///
+/// impl Log for Queue {
+///     // ..
+/// #   type Error = ();
+/// #
+/// #   fn log(&mut self, byte: u8) -> Result<(), ()> {
+/// #       *self.buffer.get_mut(self.len).ok_or(())? = byte;
+/// #       self.len += 1;
+/// #       Ok(())
+/// #   }
+/// }
+///
+/// struct Logger {
+///     queue: Queue,
+///     dropped: Dropped,
+/// }
+///
+/// impl Log for Logger {
+///     type Error = ();
+///
+///     fn log(&mut self, byte: u8) -> Result<(), ()> {
+///         self.log_bytes(&[byte])
+///     }
+///
+///     fn log_bytes(&mut self, bytes: &[u8]) -> Result<(), ()> {
+///         // report the gap right before this record but only if there's space for both
+///         let needed = if self.dropped.pending() == 0 {
+///             bytes.len()
+///         } else {
+///             Dropped::MAX_LEN + bytes.len()
+///         };
+///
+///         if self.queue.free() < needed {
+///             self.dropped.incr();
+///             return Ok(());
+///         }
+///
+///         self.dropped.flush(&mut self.queue)?;
+///         for byte in bytes {
+///             self.queue.log(*byte)?;
+///         }
+///
+///         Ok(())
+///     }
+/// }
+/// ```
+pub struct Dropped {
+    pending: u32,
+    total: u32,
+}
+
+impl Dropped {
+    /// Maximum number of bytes that `flush` will send through the logger
+    ///
+    /// This is the length of the record itself. If the logger prefixes each record, like
+    /// [`Timestamped`](struct.Timestamped.html) does with up to 5 bytes of timestamp, the prefix
+    /// must be accounted for separately.
+    pub const MAX_LEN: usize = 6;
+
+    /// Creates a new counter
+    pub const fn new() -> Self {
+        Dropped {
+            pending: 0,
+            total: 0,
+        }
+    }
+
+    /// Records that one message was discarded
+    pub fn incr(&mut self) {
+        self.pending = self.pending.saturating_add(1);
+        self.total = self.total.saturating_add(1);
+    }
+
+    /// Returns the number of discarded messages that have not yet been reported
+    pub fn pending(&self) -> u32 {
+        self.pending
+    }
+
+    /// Returns the total number of messages discarded since this counter was created
+    ///
+    /// This value is not affected by `flush` and can be used for health telemetry.
+    pub fn total(&self) -> u32 {
+        self.total
+    }
+
+    /// Reports the pending discarded messages through the given `logger`
+    ///
+    /// This does nothing if there are no pending discarded messages. The pending count is only
+    /// cleared if the `logger` doesn't return an error.
+    pub fn flush<L>(&mut self, logger: &mut L) -> Result<(), L::Error>
+    where
+        L: Log + ?Sized,
+    {
+        if self.pending == 0 {
+            return Ok(());
+        }
+
+        log_message(logger, crate::id(&DROPPED), &[self.pending])?;
+
+        self.pending = 0;
+
+        Ok(())
+    }
+}
+
+impl Default for Dropped {
+    fn default() -> Self {
+        Dropped::new()
+    }
+}
@@ -0,0 +1,178 @@
+//! Types whose values are logged as their packed fields
+
+use crate::{Buffer, GlobalLog, Log};
+
+#[export_name = "record too long"]
+#[link_section = ".stlog.internal"]
+static TOO_LONG: u8 = 0;
+
+/// Capacity of the buffer a record with arguments is built on
+pub(crate) const CAPACITY: usize = 64;
+
+/// A type whose values can be passed as arguments of the logging macros
+///
+/// This trait is implemented using `#[derive(Format)]` on structs and tuple structs, and by
+/// `#[derive(Intern)]` on fieldless enums; don't implement it by hand.
+///
+/// The layout of the struct, its path and the names and types of its fields, is interned in the
+/// `.stlog` section; only the ID of the layout and the values of the fields are sent. Integers are
+/// sent using a variable length encoding, so small values take a single byte, and `bool`s take one
+/// byte. Fields must be integers, up to 64 bits wide, `bool`s or types that implement `Format`. The
+/// types of the fields are read from the struct declaration so type aliases of integers can't be
+/// used.
+///
+/// A record, the ID of the message followed by its arguments, can be at most 64 bytes long; records
+/// that don't fit are replaced by an internal "record too long" record. Unlike the "N messages
+/// dropped" records of [`Dropped`](struct.Dropped.html), which report messages that a logger had
+/// no room for, this reports a message that was never sent because its arguments were too big.
+///
+/// Like `#[derive(Intern)]`, this derive requires Rust 1.54 or newer.
+///
+/// # Example
+///
+/// ```
+/// use stlog::{info, Format, Intern, Log};
+///
+/// #[derive(Clone, Copy, Intern)]
+/// enum Parity {
+///     None,
+///     Even,
+///     Odd,
+/// }
+///
+/// #[derive(Format)]
+/// struct Config {
+///     baud: u32,
+///     stop_bits: u8,
+///     parity: Parity,
+/// }
+///
+/// struct Logger;
+///
+/// impl Log for Logger {
+///     // ..
+/// #   type Error = ();
+/// #
+/// #   fn log(&mut self, _: u8) -> Result<(), ()> {
+/// #       Ok(())
+/// #   }
+/// }
+///
+/// fn configure(logger: &mut Logger, config: &Config) -> Result<(), ()> {
+///     // decoded as "config = Config { baud: 115200, stop_bits: 1, parity: None }"
+///     info!(*logger, "config = {}", config)
+/// }
+/// #
+/// # let config = Config { baud: 115_200, stop_bits: 1, parity: Parity::None };
+/// # configure(&mut Logger, &config).unwrap();
+/// ```
+///
+/// The derive refers to the `stlog` crate as `stlog`. If the crate has been renamed, or is
+/// re-exported by another crate, pass its path using the `stlog` attribute, e.g.
+/// `#[stlog(crate = hal::stlog)]`.
+pub trait Format {
+    #[doc(hidden)]
+    fn format(&self, f: &mut Formatter);
+}
+
+impl<T> Format for &T
+where
+    T: Format + ?Sized,
+{
+    fn format(&self, f: &mut Formatter) {
+        T::format(*self, f)
+    }
+}
+
+/// A record with arguments built on the stack
+#[doc(hidden)]
+pub struct Formatter {
+    bytes: [u8; CAPACITY],
+    len: usize,
+    overflow: bool,
+}
+
+impl Formatter {
+    /// Starts the record of the message at `address`
+    pub fn new(address: u8) -> Self {
+        let mut f = Formatter {
+            bytes: [0; CAPACITY],
+            len: 0,
+            overflow: false,
+        };
+        f.push(address);
+        f
+    }
+
+    /// Writes an interned ID
+    pub fn write_id(&mut self, id: u8) {
+        self.write_u64(u64::from(id))
+    }
+
+    /// Writes `n` using the unsigned LEB128 encoding
+    pub fn write_u64(&mut self, mut n: u64) {
+        loop {
+            let byte = (n & 0x7f) as u8;
+            n >>= 7;
+
+            if n == 0 {
+                return self.push(byte);
+            }
+
+            self.push(byte | 0x80);
+        }
+    }
+
+    /// Writes `n` using the zigzag encoding, so small negative numbers also take few bytes
+    pub fn write_i64(&mut self, n: i64) {
+        self.write_u64(((n << 1) ^ (n >> 63)) as u64)
+    }
+
+    /// Writes `b` as a single byte
+    pub fn write_bool(&mut self, b: bool) {
+        self.push(b as u8)
+    }
+
+    fn push(&mut self, byte: u8) {
+        if self.overflow {
+            return;
+        }
+
+        match self.bytes.get_mut(self.len) {
+            Some(slot) => {
+                *slot = byte;
+                self.len += 1;
+            }
+            None => self.discard(),
+        }
+    }
+
+    /// Replaces the record, which doesn't fit in the buffer, with a "record too long" record
+    fn discard(&mut self) {
+        let record = Buffer::message(crate::id(&TOO_LONG), &[]);
+        let record = record.as_slice();
+
+        self.bytes[..record.len()].copy_from_slice(record);
+        self.len = record.len();
+        self.overflow = true;
+    }
+
+    pub(crate) fn as_slice(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+/// Sends the record built by `f`
+pub(crate) fn log_formatted<L>(logger: &mut L, f: &Formatter) -> Result<(), L::Error>
+where
+    L: Log + ?Sized,
+{
+    logger.begin()?;
+    logger.log_bytes(f.as_slice())
+}
+
+/// Global version of `log_formatted`
+#[doc(hidden)]
+pub fn global_log_formatted(logger: &dyn GlobalLog, f: &Formatter) {
+    logger.log_bytes(f.as_slice())
+}
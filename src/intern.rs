@@ -0,0 +1,60 @@
+//! Enums whose variants are logged as interned names
+
+/// A fieldless enum whose variant names are interned in the `.stlog` section
+///
+/// This trait is implemented using `#[derive(Intern)]`; don't implement it by hand. The values of
+/// these enums can be passed as arguments of the logging macros: each argument replaces a `{}` in
+/// the message and is sent as the ID of the name of its variant, which takes one or two bytes.
+/// The derive also implements [`Format`](trait.Format.html) so these enums can be the fields of
+/// `#[derive(Format)]` structs.
+///
+/// The derive interns the variant names under the path of the enum, e.g. `app::State::Connecting`,
+/// so it requires Rust 1.54 or newer; the decoder only prints the name of the variant.
+///
+/// # Example
+///
+/// ```
+/// use stlog::{info, Intern, Log};
+///
+/// #[derive(Clone, Copy, Intern)]
+/// enum State {
+///     Idle,
+///     Connecting,
+///     Connected,
+/// }
+///
+/// struct Logger;
+///
+/// impl Log for Logger {
+///     // ..
+/// #   type Error = ();
+/// #
+/// #   fn log(&mut self, _: u8) -> Result<(), ()> {
+/// #       Ok(())
+/// #   }
+/// }
+///
+/// fn transition(logger: &mut Logger, state: State) -> Result<(), ()> {
+///     // decoded as "state = Connecting"
+///     info!(*logger, "state = {}", state)
+/// }
+/// #
+/// # transition(&mut Logger, State::Connecting).unwrap();
+/// ```
+///
+/// The derive refers to the `stlog` crate as `stlog`. If the crate has been renamed, or is
+/// re-exported by another crate, pass its path using the `stlog` attribute, e.g.
+/// `#[stlog(crate = hal::stlog)]`.
+pub trait Intern {
+    /// Returns the ID of the name of this variant
+    fn id(&self) -> u8;
+}
+
+impl<T> Intern for &T
+where
+    T: Intern + ?Sized,
+{
+    fn id(&self) -> u8 {
+        T::id(*self)
+    }
+}
@@ -0,0 +1,171 @@
+//! Loggers for hosted targets
+
+use std::{
+    fs::File,
+    io::{self, Write},
+    path::Path,
+    sync::{Mutex, MutexGuard},
+};
+
+use crate::{GlobalLog, Log};
+
+/// A logger that writes to a `std::io::Write` implementer
+///
+/// # Example
+///
+/// ```
+/// use stlog::{info, IoLogger};
+///
+/// let mut logger = IoLogger::new(vec![]);
+///
+/// info!(logger, "Hello, world!").unwrap();
+///
+/// assert_eq!(logger.free().len(), 1);
+/// ```
+pub struct IoLogger<W> {
+    writer: W,
+}
+
+impl<W> IoLogger<W>
+where
+    W: Write,
+{
+    /// Creates a logger that writes to the given `writer`
+    pub fn new(writer: W) -> Self {
+        IoLogger { writer }
+    }
+
+    /// Releases the writer
+    pub fn free(self) -> W {
+        self.writer
+    }
+}
+
+impl<W> Log for IoLogger<W>
+where
+    W: Write,
+{
+    type Error = io::Error;
+
+    fn log(&mut self, byte: u8) -> io::Result<()> {
+        self.writer.write_all(&[byte])
+    }
+
+    fn log_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.writer.write_all(bytes)
+    }
+}
+
+/// A global logger that writes to a `std::io::Write` implementer protected by a `Mutex`
+///
+/// I/O errors are ignored.
+///
+/// # Example
+///
+/// ```
+/// use stlog::{global_logger, info, MutexLogger};
+///
+/// #[global_logger]
+/// static LOGGER: MutexLogger<Vec<u8>> = MutexLogger::new(Vec::new());
+///
+/// fn main() {
+///     std::thread::spawn(|| info!("Hello from a thread!")).join().unwrap();
+///     info!("Hello from main!");
+///
+///     assert_eq!(LOGGER.lock().len(), 2);
+/// }
+/// ```
+pub struct MutexLogger<W> {
+    writer: Mutex<W>,
+}
+
+impl<W> MutexLogger<W> {
+    /// Creates a logger that writes to the given `writer`
+    pub const fn new(writer: W) -> Self {
+        MutexLogger {
+            writer: Mutex::new(writer),
+        }
+    }
+
+    /// Locks the writer
+    ///
+    /// No message can be logged while the returned guard is alive.
+    pub fn lock(&self) -> MutexGuard<'_, W> {
+        // a panic while holding the lock can't leave a `Write` implementer in an invalid state
+        self.writer.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<W> GlobalLog for MutexLogger<W>
+where
+    W: Write + Send,
+{
+    fn log(&self, byte: u8) {
+        self.log_bytes(&[byte])
+    }
+
+    fn log_bytes(&self, bytes: &[u8]) {
+        self.lock().write_all(bytes).ok();
+    }
+}
+
+/// A global logger that writes a decodable log stream to a file
+///
+/// Messages logged before the file is `create`-d are discarded. The file can be decoded with
+/// `stlog-decode -e <executable> <file>`.
+///
+/// # Example
+///
+/// ```
+/// use stlog::{global_logger, info, FileLogger};
+///
+/// #[global_logger]
+/// static LOGGER: FileLogger = FileLogger::new();
+///
+/// fn main() -> std::io::Result<()> {
+///     LOGGER.create(std::env::temp_dir().join("stlog.bin"))?;
+///
+///     info!("Hello, world!");
+///
+///     Ok(())
+/// }
+/// ```
+pub struct FileLogger {
+    file: MutexLogger<Option<File>>,
+}
+
+impl FileLogger {
+    /// Creates a logger that discards all messages until a file is `create`-d
+    pub const fn new() -> Self {
+        FileLogger {
+            file: MutexLogger::new(None),
+        }
+    }
+
+    /// Creates (or truncates) the file at `path` and logs all the following messages to it
+    pub fn create<P>(&self, path: P) -> io::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        *self.file.lock() = Some(File::create(path)?);
+        Ok(())
+    }
+}
+
+impl GlobalLog for FileLogger {
+    fn log(&self, byte: u8) {
+        self.log_bytes(&[byte])
+    }
+
+    fn log_bytes(&self, bytes: &[u8]) {
+        if let Some(file) = &mut *self.file.lock() {
+            file.write_all(bytes).ok();
+        }
+    }
+}
+
+impl Default for FileLogger {
+    fn default() -> Self {
+        FileLogger::new()
+    }
+}
@@ -0,0 +1,86 @@
+//! Per-level global loggers
+
+use crate::{span, throttle, GlobalLog};
+
+extern "C" {
+    static __stlog_error_start__: u8;
+    static __stlog_warning_start__: u8;
+    static __stlog_info_start__: u8;
+    static __stlog_debug_start__: u8;
+    static __stlog_trace_start__: u8;
+}
+
+/// A global logger that sends each level to a different logger
+///
+/// Created by `#[global_logger(error = .., trace = ..)]`. The internal records that follow up on a
+/// message, i.e. span "enter" / "exit" and "suppressed N times", are sent along with the message.
+/// Other internal messages and the levels that have no logger of their own are sent to the
+/// `default` logger.
+#[doc(hidden)]
+pub struct Levels {
+    pub default: &'static dyn GlobalLog,
+    pub error: Option<&'static dyn GlobalLog>,
+    pub warn: Option<&'static dyn GlobalLog>,
+    pub info: Option<&'static dyn GlobalLog>,
+    pub debug: Option<&'static dyn GlobalLog>,
+    pub trace: Option<&'static dyn GlobalLog>,
+}
+
+impl Levels {
+    /// Returns the logger the message with the given `id` must be sent to
+    fn logger(&self, id: u8) -> &'static dyn GlobalLog {
+        // NOTE the `.stlog` section is sorted by level; see `stlog.x`
+        let logger = unsafe {
+            if id < crate::id(&__stlog_error_start__) {
+                None
+            } else if id < crate::id(&__stlog_warning_start__) {
+                self.error
+            } else if id < crate::id(&__stlog_info_start__) {
+                self.warn
+            } else if id < crate::id(&__stlog_debug_start__) {
+                self.info
+            } else if id < crate::id(&__stlog_trace_start__) {
+                self.debug
+            } else {
+                self.trace
+            }
+        };
+
+        logger.unwrap_or(self.default)
+    }
+}
+
+impl GlobalLog for Levels {
+    fn log(&self, id: u8) {
+        self.logger(id).log(id)
+    }
+
+    fn log_bytes(&self, bytes: &[u8]) {
+        if let Some(id) = origin(bytes) {
+            self.logger(id).log_bytes(bytes)
+        }
+    }
+}
+
+/// Returns the ID of the message the `record` belongs to
+///
+/// That's the first byte of the record except for the internal records that follow up on another
+/// message, whose first argument is the ID of that message.
+fn origin(record: &[u8]) -> Option<u8> {
+    let id = *record.first()?;
+    let follow_ups = [
+        crate::id(&span::ENTER),
+        crate::id(&span::EXIT),
+        crate::id(&throttle::SUPPRESSED),
+    ];
+
+    if !follow_ups.contains(&id) {
+        return Some(id);
+    }
+
+    // NOTE the argument is LEB128 encoded; IDs take at most two bytes
+    Some(match record.get(1..3) {
+        Some(&[low, high]) if low & 0x80 != 0 => (low & 0x7f) | (high << 7),
+        _ => *record.get(1).unwrap_or(&id),
+    })
+}
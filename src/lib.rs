@@ -7,36 +7,62 @@
 //! # Features
 //!
 //! - `O(1)` execution time. Logging a message of arbitrary size is done in a constant number of
-//! instructions.
+//!   instructions.
 //!
 //! - `O(0)` memory usage. The messages are NOT stored in the target device memory (`.rodata`).
 //!
 //! - Supports different logging levels: error, warning, info, debug and trace, in decreasing level
-//! of severity. By default, the `dev` profile logs debug, and more severe, messages and the
-//! `release` profile logs info, and more severe, messages, but this can changed using the Cargo
-//! features of this crate.
+//!   of severity. By default, the `dev` profile logs debug, and more severe, messages and the
+//!   `release` profile logs info, and more severe, messages, but this can changed using the Cargo
+//!   features of this crate.
 //!
 //! - Provides a global logging mode
 //!
+//! - Rate limited variants of the logging macros for hot loops and interrupt storms:
+//!   `error_once!`, `error_every_n!`, `error_throttled!`, etc.
+//!
+//! - Lightweight tracing: [`span!`](macro.span.html) logs when a scope is entered and exited.
+//!
+//! - Optional device side timestamps. See [`Timestamped`](struct.Timestamped.html).
+//!
+//! - Optional firmware identity handshake. See [`Session`](struct.Session.html).
+//!
+//! - A [`CrashLog`](struct.CrashLog.html) whose records survive soft resets.
+//!
+//! - Enum values can be logged as interned variant names using `#[derive(Intern)]`, and structs as
+//!   the packed values of their fields using `#[derive(Format)]`. See [`Intern`](trait.Intern.html)
+//!   and [`Format`](trait.Format.html).
+//!
+//! - Buffered loggers can report the messages they had to discard using
+//!   [`Dropped`](struct.Dropped.html). The decoder displays these as gaps in the log.
+//!
 //! # Non-features
 //!
-//! - `printf` style or any other kind of formatting
+//! - `printf` style or any other kind of formatting, besides replacing `{}` with a value that
+//!   implements [`Format`](trait.Format.html)
 //!
 //! # MSRV
 //!
-//! This crate is guaranteed to compile on stable Rust 1.31 and up. It might compile on older
+//! This crate is guaranteed to compile on stable Rust 1.34 and up. It might compile on older
 //! versions but that may change in any new patch release.
 //!
+//! The `critical-section` Cargo feature raises the MSRV to 1.57, the `embedded-hal` feature to
+//! 1.57, the `embedded-io` feature to 1.60, the `std` feature to 1.63, the `async` feature to
+//! 1.75 and the `spanned` feature to 1.88. `#[derive(Intern)]` and `#[derive(Format)]` require
+//! Rust 1.54 or newer.
+//!
 //! # Known limitations
 //!
-//! - The current implementation only supports 256 different log strings. This restriction may be
-//! lifted in the future.
+//! - The current implementation only supports 256 different log strings, including the variant
+//!   names and struct layouts interned by `#[derive(Intern)]` and `#[derive(Format)]`. This
+//!   restriction may be lifted in the future.
 //!
 //! - The string should not contain the character `@`. Any text that follows this character will be
-//! discarded.
+//!   discarded.
 //!
-//! - The exact same string can't be used in two or more macro invocations. Enabling the `spanned`
-//! Cargo feature removes this limitation.
+//! - The exact same string can't be used in two or more macro invocations; this is reported when
+//!   the crate is compiled, or by the linker if the invocations are in different crates. Enabling
+//!   the `spanned` Cargo feature removes this limitation.
 //!
 //! ``` ignore
 //! use stlog::{error, info};
@@ -75,9 +101,17 @@
 //! ]
 //! ```
 //!
-//! To decode the logs on the host you'll need version v0.2.x of the [`stcat`] tool.
+//! The same flag works when the application is built for a hosted ELF target, e.g. a simulation
+//! build for `x86_64-unknown-linux-gnu`: on these targets the linker script augments the default
+//! linker script instead of replacing it, and message IDs are computed relative to the start of the
+//! `.stlog` section so they are correct even if the executable is relocated at load time.
+//!
+//! To decode the logs on the host you'll need version v0.2.x of the [`stcat`] tool, or the
+//! `stlog-decode` tool that's part of the [`stlog-decoder`] crate. `stcat` doesn't understand the
+//! internal messages, like "N messages dropped", that this crate may emit.
 //!
 //! [`stcat`]: https://crates.io/crates/stcat
+//! [`stlog-decoder`]: https://crates.io/crates/stlog-decoder
 //!
 //! # Examples
 //!
@@ -151,15 +185,80 @@
 //! }
 //! ```
 //!
+//! The global logger can also be selected, and swapped, at runtime: use a
+//! [`RuntimeLogger`](struct.RuntimeLogger.html) as the `global_logger` and call
+//! [`set_global_logger`](fn.set_global_logger.html). These require the `critical-section` feature.
+//!
+//! If `stlog` is re-exported by another crate, e.g. a HAL, or has been renamed, pass its path to
+//! the attribute: `#[hal::stlog::global_logger(crate = hal::stlog)]`.
+//!
+//! Levels can be sent to other global loggers by naming them in the arguments of the attribute.
+//! The levels that are not named, and internal messages like "N messages dropped", are sent to the
+//! annotated `static`. The internal records that follow up on a message, the "enter" and "exit"
+//! records of a `span!` and the "suppressed N times" records of the `*_throttled!` macros, are sent
+//! to the logger of that message.
+//!
+//! ``` ignore
+//! static UART_LOGGER: Uart = Uart::new();
+//! static RTT_LOGGER: Rtt = Rtt::new();
+//!
+//! // errors go to the UART, trace messages to RTT and everything else to `LOGGER`
+//! #[global_logger(error = UART_LOGGER, trace = RTT_LOGGER)]
+//! static LOGGER: RingBuffer<1024> = RingBuffer::new();
+//! ```
+//!
 //! # Cargo features
 //!
+//! ## `async`
+//!
+//! Enabling this feature adds an [`AsyncLog`](trait.AsyncLog.html) trait for transports that can
+//! await buffer space, like DMA driven UARTs. `info!(logger, "..").await` logs through an
+//! `AsyncLog` implementer.
+//!
+//! ## `critical-section`
+//!
+//! Enabling this feature adds a [`RingBuffer`](struct.RingBuffer.html) global logger that keeps
+//! the most recent records in RAM, for post-mortem decoding from a memory dump, a
+//! [`Counters`](struct.Counters.html) global logger that counts messages instead of sending them,
+//! a [`OnceLogger`](struct.OnceLogger.html) global logger that wraps a logger initialized at
+//! runtime, and a [`RuntimeLogger`](struct.RuntimeLogger.html) global logger that forwards to a
+//! logger set, and swapped, at runtime.
+//! The application must provide an implementation of the [`critical-section`] crate.
+//!
+//! [`critical-section`]: https://crates.io/crates/critical-section
+//!
+//! ## `embedded-hal` and `embedded-io`
+//!
+//! These features add, under the [`serial`](serial/index.html) module, ready-made loggers that
+//! write to an `embedded_hal::serial::Write<u8>` (v0.2) or an `embedded_io::Write` (v0.6)
+//! implementer. Both blocking and buffered, non-blocking, loggers are provided.
+//!
+//! ## `std`
+//!
+//! Enabling this feature adds loggers for hosted targets, e.g. simulation builds of the firmware:
+//! [`IoLogger`](struct.IoLogger.html) writes to a `std::io::Write` implementer,
+//! [`MutexLogger`](struct.MutexLogger.html) is a thread-safe global logger and
+//! [`FileLogger`](struct.FileLogger.html) is a global logger that writes a log file that can be
+//! decoded with `stlog-decode`.
+//!
+//! ## `null-logger`
+//!
+//! Library crates that log through the global logger force all the applications that depend on them
+//! to declare a `global_logger`, including unit tests and applications that don't care about logs.
+//! Enabling this feature makes the [`NullLogger`](struct.NullLogger.html) the global logger of
+//! applications that don't declare one; a `global_logger` declared by the application overrides it.
+//!
+//! On targets without an OS the fallback is provided by the `stlog.x` linker script so it only works
+//! when the application is linked using it. On hosted targets that use ELF, like Linux, it also
+//! works without the linker script, e.g. in unit tests.
+//!
 //! ## `spanned`
 //!
 //! Enabling this feature adds variants of the macros, that include span information, under the
 //! `spanned` module. For example, `spanned::info!("Hello")` will log the string `"Hello, loc:
 //! src/main.rs:12"`, where `src/main.rs:12` is the location of the macro invocation.
 //!
-//! This feature depends on unstable `proc_macro` features and requires a nightly compiler.
+//! These macros, like all the other macros, work when `stlog` is re-exported by another crate.
 //!
 //! ## `[release-]max-level-{off,error,warning,info,debug,trace}`
 //!
@@ -209,17 +308,104 @@
 //!   = note: "rust-lld" (..)
 //!   = note: rust-lld: error: undefined symbol: stlog::GLOBAL_LOGGER
 //! ```
+//!
+//! Declare a `global_logger` or, if the application doesn't care about logs, enable the
+//! `null-logger` feature.
+//!
+//! ## The firmware has no build ID
+//!
+//! Symptom: you'll get an error when linking, for a target without an OS, a program that calls
+//! `start_session` or uses `Session`
+//!
+//! ``` text
+//! $ cargo build
+//! error: linking with `rust-lld` failed: exit code: 1
+//!   |
+//!   = note: "rust-lld" (..)
+//!   = note: rust-lld: error: stlog.x:30: undefined section .note.gnu.build-id
+//! ```
+//!
+//! Pass the `--build-id` flag to the linker, e.g. `-C link-arg=--build-id`, and place the
+//! `.note.gnu.build-id` section in flash.
+//!
+//! ## Declared the `global_logger` more than once
+//!
+//! Symptom: you'll get an error when linking the program. The sections of the duplicated symbol
+//! name the `static`s that were declared as the global logger.
+//!
+//! ``` text
+//! $ cargo build
+//! error: linking with `rust-lld` failed: exit code: 1
+//!   |
+//!   = note: "rust-lld" (..)
+//!   = note: rust-lld: error: duplicate symbol: stlog::GLOBAL_LOGGER
+//!           >>> defined at main.rs:10 (src/main.rs:10)
+//!           >>>            app-(..).o:(.rodata.stlog::GLOBAL_LOGGER declared by `app::LOGGER`+0x0)
+//!           >>> defined at lib.rs:5 (src/lib.rs:5)
+//!           >>>            liba-(..).o:(.rodata.stlog::GLOBAL_LOGGER declared by `liba::LOGGER`+0x0)
+//! ```
+//!
+//! Only the top crate should declare a `global_logger`; remove the other declaration.
 
 #![deny(rust_2018_compatibility)]
 #![deny(rust_2018_idioms)]
 #![deny(warnings)]
 #![no_std]
 
-pub use stlog_macros::global_logger;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[doc(hidden)]
+pub use stlog_macros::message as __message;
+pub use stlog_macros::{global_logger, Format, Intern};
 use void::Void;
 
+#[cfg(feature = "async")]
+pub use crate::async_log::AsyncLog;
+#[cfg(feature = "std")]
+pub use crate::io::{FileLogger, IoLogger, MutexLogger};
+#[cfg(feature = "critical-section")]
+pub use crate::{
+    counters::Counters,
+    once::OnceLogger,
+    ring::RingBuffer,
+    runtime::{set_global_logger, RuntimeLogger},
+};
+pub use crate::{
+    crash::CrashLog,
+    dropped::Dropped,
+    format::Format,
+    intern::Intern,
+    session::{start_session, Session},
+    span::Span,
+    timestamp::{Timestamp, Timestamped},
+};
+
+#[cfg(feature = "async")]
+mod async_log;
+#[cfg(feature = "critical-section")]
+mod counters;
+mod crash;
+mod dropped;
+mod format;
+mod intern;
+#[cfg(feature = "std")]
+mod io;
+mod levels;
+#[cfg(feature = "critical-section")]
+mod once;
+#[cfg(feature = "critical-section")]
+mod ring;
+#[cfg(feature = "critical-section")]
+mod runtime;
+#[cfg(any(feature = "embedded-hal", feature = "embedded-io"))]
+pub mod serial;
+mod session;
+mod span;
 #[cfg(feature = "spanned")]
 pub mod spanned;
+mod throttle;
+mod timestamp;
 
 /// A logger that does nothing
 pub struct NullLogger;
@@ -228,6 +414,11 @@ impl GlobalLog for NullLogger {
     fn log(&self, _: u8) {}
 }
 
+#[cfg(feature = "null-logger")]
+#[export_name = "__stlog_null_logger"]
+#[used]
+static NULL_LOGGER: &dyn GlobalLog = &NullLogger;
+
 impl Log for NullLogger {
     type Error = Void;
 
@@ -242,8 +433,26 @@ impl Log for NullLogger {
 /// method is synchronized with other invocations of itself that could occur concurrently. Also,
 /// note that there the return type is `()` and not `Result` so errors must be handled by the `log`
 /// method.
+///
+/// Each call to `log` or `log_bytes` is a complete record: the logging macros use `log` for records
+/// that are a single byte long and `log_bytes` for longer ones. The macros don't call
+/// [`Log::begin`](trait.Log.html#method.begin) on global loggers, so wrappers like
+/// [`Timestamped`](struct.Timestamped.html) don't see the start of the records unless the
+/// implementation tells them: implementations that forward to a [`Log`](trait.Log) implementer
+/// should call `Log::begin` before `Log::log` or `Log::log_bytes` and must override `log_bytes`.
 pub trait GlobalLog: Sync {
     fn log(&self, address: u8);
+
+    /// Sends a record that spans several bytes, e.g. a message followed by its arguments
+    ///
+    /// The default implementation calls `log` once per byte, which splits the record into several
+    /// one-byte records. Implementations should override it to send the whole record within a
+    /// single critical section; otherwise records logged concurrently may get interleaved.
+    fn log_bytes(&self, bytes: &[u8]) {
+        for byte in bytes {
+            self.log(*byte);
+        }
+    }
 }
 
 /// A logger that encodes messages using a symbol table
@@ -258,25 +467,168 @@ pub trait Log {
 
     /// Sends the `address` of the symbol through some interface
     fn log(&mut self, address: u8) -> Result<(), Self::Error>;
+
+    /// Marks the start of a new record
+    ///
+    /// The logging macros call this method right before sending the address of a symbol. The
+    /// default implementation does nothing; wrappers like [`Timestamped`](struct.Timestamped.html)
+    /// use it to prefix each record with extra data.
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Sends a whole record: the address of a symbol followed by its arguments, if any
+    ///
+    /// The logging macros build each record on the stack and hand it over to this method in a
+    /// single call, right after calling `begin`. The default implementation calls `log` once per
+    /// byte; loggers backed by a DMA capable transport can override it to queue the record as a
+    /// whole.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stlog::{info, Log, Timestamp, Timestamped};
+    ///
+    /// struct Dma {
+    ///     transfers: usize,
+    /// }
+    ///
+    /// impl Log for Dma {
+    ///     type Error = ();
+    ///
+    ///     fn log(&mut self, byte: u8) -> Result<(), ()> {
+    ///         self.log_bytes(&[byte])
+    ///     }
+    ///
+    ///     fn log_bytes(&mut self, bytes: &[u8]) -> Result<(), ()> {
+    ///         // queue a DMA transfer of `bytes`
+    ///         // ..
+    ///         self.transfers += 1;
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// struct Clock;
+    ///
+    /// impl Timestamp for Clock {
+    ///     fn now(&mut self) -> u32 {
+    ///         // ..
+    /// #       0
+    ///     }
+    /// }
+    ///
+    /// let mut logger = Timestamped::new(Dma { transfers: 0 }, Clock);
+    ///
+    /// info!(logger, "Hello").unwrap();
+    /// info!(logger, "World").unwrap();
+    ///
+    /// // one transfer for the "timestamp sync" record and one per message
+    /// assert_eq!(logger.free().0.transfers, 3);
+    /// ```
+    fn log_bytes(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        for byte in bytes {
+            self.log(*byte)?;
+        }
+
+        Ok(())
+    }
 }
 
 /// Logs the given string literal at the ERROR log level
 ///
-/// `$logger` must be an expression whose type implements the [`Log`](trait.Log.html) trait.
+/// `$logger` must be an expression whose type implements the [`Log`](trait.Log.html) trait. With
+/// the `async` Cargo feature enabled, `$logger` may instead implement the `AsyncLog` trait, in
+/// which case the macro returns a future that must be `.await`-ed. `$logger` is only evaluated if
+/// the level of the message is enabled.
 ///
 /// If `$logger` is omitted the global logger will be used.
+///
+/// `$string` may also be a macro that expands to a string literal, like `concat!` or `stringify!`;
+/// this requires Rust 1.54 or newer. `const` strings can't be used because the string must be
+/// known when the macro is expanded.
+///
+/// The string may be followed by values of types that implement the [`Format`](trait.Format.html)
+/// trait, one per `{}` in the string, e.g. `error!(logger, "state = {}", state)`. Only string
+/// literals can be followed by arguments when `$logger` is omitted.
+///
+/// # Example
+///
+/// ```
+/// use stlog::{error, Log};
+///
+/// struct Logger;
+///
+/// impl Log for Logger {
+///     // ..
+/// #   type Error = ();
+/// #
+/// #   fn log(&mut self, _: u8) -> Result<(), ()> {
+/// #       Ok(())
+/// #   }
+/// }
+///
+/// fn init(logger: &mut Logger) -> Result<(), ()> {
+///     // logs "my_crate::driver: init failed"
+///     error!(*logger, concat!(module_path!(), ": init failed"))?;
+///     error!(*logger, stringify!(init(logger)))
+/// }
+/// #
+/// # init(&mut Logger).unwrap();
+/// #
+/// # let (mut logger, mut evaluated) = (Logger, false);
+/// # stlog::trace!(*{ evaluated = true; &mut logger }, "disabled").unwrap();
+/// # assert_eq!(evaluated, stlog::max_level() as u8 >= stlog::Level::Trace as u8);
+/// ```
 #[macro_export]
 macro_rules! error {
-    ($logger:expr, $string:expr) => {{
+    ($string:literal, $($arg:expr),+) => {
         if $crate::max_level() as u8 >= $crate::Level::Error as u8 {
-            #[export_name = $string]
-            #[link_section = ".stlog.error"]
+            extern "Rust" {
+                #[link_name = "stlog::GLOBAL_LOGGER"]
+                static LOGGER: &'static dyn $crate::GlobalLog;
+            }
+
+            #[$crate::__message(".stlog.error", $string, $($arg),+)]
             static SYMBOL: u8 = 0;
 
-            $crate::Log::log(&mut $logger, &SYMBOL as *const u8 as usize as u8)
-        } else {
-            Ok(())
+            let mut f = $crate::Formatter::new($crate::id(&SYMBOL));
+            $($crate::Format::format(&$arg, &mut f);)+
+            $crate::global_log_formatted(unsafe { LOGGER }, &f)
         }
+    };
+
+    ($logger:expr, $string:expr, $($arg:expr),+) => {{
+        #[allow(unused_imports)]
+        use $crate::dispatch::prelude::*;
+
+        let (record, logger) = if $crate::max_level() as u8 >= $crate::Level::Error as u8 {
+            #[$crate::__message(".stlog.error", $string, $($arg),+)]
+            static SYMBOL: u8 = 0;
+
+            let mut f = $crate::Formatter::new($crate::id(&SYMBOL));
+            $($crate::Format::format(&$arg, &mut f);)+
+            (Some(f), Some(&mut $logger))
+        } else {
+            (None, None)
+        };
+
+        (&mut &mut $crate::dispatch::Logger::new(logger)).record_args(record)
+    }};
+
+    ($logger:expr, $string:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::dispatch::prelude::*;
+
+        let (id, logger) = if $crate::max_level() as u8 >= $crate::Level::Error as u8 {
+            #[$crate::__message(".stlog.error", $string)]
+            static SYMBOL: u8 = 0;
+
+            (Some($crate::id(&SYMBOL)), Some(&mut $logger))
+        } else {
+            (None, None)
+        };
+
+        (&mut &mut $crate::dispatch::Logger::new(logger)).record(id)
     }};
 
     ($string:expr) => {
@@ -284,14 +636,13 @@ macro_rules! error {
             if $crate::max_level() as u8 >= $crate::Level::Error as u8 {
                 extern "Rust" {
                     #[link_name = "stlog::GLOBAL_LOGGER"]
-                    static LOGGER: &'static $crate::GlobalLog;
+                    static LOGGER: &'static dyn $crate::GlobalLog;
                 }
 
-                #[export_name = $string]
-                #[link_section = ".stlog.error"]
+                #[$crate::__message(".stlog.error", $string)]
                 static SYMBOL: u8 = 0;
 
-                $crate::GlobalLog::log(LOGGER, &SYMBOL as *const u8 as usize as u8)
+                $crate::GlobalLog::log(LOGGER, $crate::id(&SYMBOL))
             }
         }
     };
@@ -302,16 +653,54 @@ macro_rules! error {
 /// For more details see the [`error!`](macro.error.html) macro.
 #[macro_export]
 macro_rules! warn {
-    ($logger:expr, $string:expr) => {{
+    ($string:literal, $($arg:expr),+) => {
         if $crate::max_level() as u8 >= $crate::Level::Warn as u8 {
-            #[export_name = $string]
-            #[link_section = ".stlog.warn"]
+            extern "Rust" {
+                #[link_name = "stlog::GLOBAL_LOGGER"]
+                static LOGGER: &'static dyn $crate::GlobalLog;
+            }
+
+            #[$crate::__message(".stlog.warn", $string, $($arg),+)]
             static SYMBOL: u8 = 0;
 
-            $crate::Log::log(&mut $logger, &SYMBOL as *const u8 as usize as u8)
-        } else {
-            Ok(())
+            let mut f = $crate::Formatter::new($crate::id(&SYMBOL));
+            $($crate::Format::format(&$arg, &mut f);)+
+            $crate::global_log_formatted(unsafe { LOGGER }, &f)
         }
+    };
+
+    ($logger:expr, $string:expr, $($arg:expr),+) => {{
+        #[allow(unused_imports)]
+        use $crate::dispatch::prelude::*;
+
+        let (record, logger) = if $crate::max_level() as u8 >= $crate::Level::Warn as u8 {
+            #[$crate::__message(".stlog.warn", $string, $($arg),+)]
+            static SYMBOL: u8 = 0;
+
+            let mut f = $crate::Formatter::new($crate::id(&SYMBOL));
+            $($crate::Format::format(&$arg, &mut f);)+
+            (Some(f), Some(&mut $logger))
+        } else {
+            (None, None)
+        };
+
+        (&mut &mut $crate::dispatch::Logger::new(logger)).record_args(record)
+    }};
+
+    ($logger:expr, $string:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::dispatch::prelude::*;
+
+        let (id, logger) = if $crate::max_level() as u8 >= $crate::Level::Warn as u8 {
+            #[$crate::__message(".stlog.warn", $string)]
+            static SYMBOL: u8 = 0;
+
+            (Some($crate::id(&SYMBOL)), Some(&mut $logger))
+        } else {
+            (None, None)
+        };
+
+        (&mut &mut $crate::dispatch::Logger::new(logger)).record(id)
     }};
 
     ($string:expr) => {
@@ -319,14 +708,13 @@ macro_rules! warn {
             if $crate::max_level() as u8 >= $crate::Level::Warn as u8 {
                 extern "Rust" {
                     #[link_name = "stlog::GLOBAL_LOGGER"]
-                    static LOGGER: &'static $crate::GlobalLog;
+                    static LOGGER: &'static dyn $crate::GlobalLog;
                 }
 
-                #[export_name = $string]
-                #[link_section = ".stlog.warn"]
+                #[$crate::__message(".stlog.warn", $string)]
                 static SYMBOL: u8 = 0;
 
-                $crate::GlobalLog::log(LOGGER, &SYMBOL as *const u8 as usize as u8)
+                $crate::GlobalLog::log(LOGGER, $crate::id(&SYMBOL))
             }
         }
     };
@@ -337,16 +725,54 @@ macro_rules! warn {
 /// For more details see the [`error!`](macro.error.html) macro.
 #[macro_export]
 macro_rules! info {
-    ($logger:expr, $string:expr) => {{
+    ($string:literal, $($arg:expr),+) => {
         if $crate::max_level() as u8 >= $crate::Level::Info as u8 {
-            #[export_name = $string]
-            #[link_section = ".stlog.info"]
+            extern "Rust" {
+                #[link_name = "stlog::GLOBAL_LOGGER"]
+                static LOGGER: &'static dyn $crate::GlobalLog;
+            }
+
+            #[$crate::__message(".stlog.info", $string, $($arg),+)]
             static SYMBOL: u8 = 0;
 
-            $crate::Log::log(&mut $logger, &SYMBOL as *const u8 as usize as u8)
-        } else {
-            Ok(())
+            let mut f = $crate::Formatter::new($crate::id(&SYMBOL));
+            $($crate::Format::format(&$arg, &mut f);)+
+            $crate::global_log_formatted(unsafe { LOGGER }, &f)
         }
+    };
+
+    ($logger:expr, $string:expr, $($arg:expr),+) => {{
+        #[allow(unused_imports)]
+        use $crate::dispatch::prelude::*;
+
+        let (record, logger) = if $crate::max_level() as u8 >= $crate::Level::Info as u8 {
+            #[$crate::__message(".stlog.info", $string, $($arg),+)]
+            static SYMBOL: u8 = 0;
+
+            let mut f = $crate::Formatter::new($crate::id(&SYMBOL));
+            $($crate::Format::format(&$arg, &mut f);)+
+            (Some(f), Some(&mut $logger))
+        } else {
+            (None, None)
+        };
+
+        (&mut &mut $crate::dispatch::Logger::new(logger)).record_args(record)
+    }};
+
+    ($logger:expr, $string:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::dispatch::prelude::*;
+
+        let (id, logger) = if $crate::max_level() as u8 >= $crate::Level::Info as u8 {
+            #[$crate::__message(".stlog.info", $string)]
+            static SYMBOL: u8 = 0;
+
+            (Some($crate::id(&SYMBOL)), Some(&mut $logger))
+        } else {
+            (None, None)
+        };
+
+        (&mut &mut $crate::dispatch::Logger::new(logger)).record(id)
     }};
 
     ($string:expr) => {
@@ -354,14 +780,13 @@ macro_rules! info {
             if $crate::max_level() as u8 >= $crate::Level::Info as u8 {
                 extern "Rust" {
                     #[link_name = "stlog::GLOBAL_LOGGER"]
-                    static LOGGER: &'static $crate::GlobalLog;
+                    static LOGGER: &'static dyn $crate::GlobalLog;
                 }
 
-                #[export_name = $string]
-                #[link_section = ".stlog.info"]
+                #[$crate::__message(".stlog.info", $string)]
                 static SYMBOL: u8 = 0;
 
-                $crate::GlobalLog::log(LOGGER, &SYMBOL as *const u8 as usize as u8)
+                $crate::GlobalLog::log(LOGGER, $crate::id(&SYMBOL))
             }
         }
     };
@@ -372,16 +797,54 @@ macro_rules! info {
 /// For more details see the [`error!`](macro.error.html) macro.
 #[macro_export]
 macro_rules! debug {
-    ($log:expr, $string:expr) => {{
+    ($string:literal, $($arg:expr),+) => {
         if $crate::max_level() as u8 >= $crate::Level::Debug as u8 {
-            #[export_name = $string]
-            #[link_section = ".stlog.debug"]
+            extern "Rust" {
+                #[link_name = "stlog::GLOBAL_LOGGER"]
+                static LOGGER: &'static dyn $crate::GlobalLog;
+            }
+
+            #[$crate::__message(".stlog.debug", $string, $($arg),+)]
             static SYMBOL: u8 = 0;
 
-            $crate::Log::log(&mut $log, &SYMBOL as *const u8 as usize as u8)
-        } else {
-            Ok(())
+            let mut f = $crate::Formatter::new($crate::id(&SYMBOL));
+            $($crate::Format::format(&$arg, &mut f);)+
+            $crate::global_log_formatted(unsafe { LOGGER }, &f)
         }
+    };
+
+    ($logger:expr, $string:expr, $($arg:expr),+) => {{
+        #[allow(unused_imports)]
+        use $crate::dispatch::prelude::*;
+
+        let (record, logger) = if $crate::max_level() as u8 >= $crate::Level::Debug as u8 {
+            #[$crate::__message(".stlog.debug", $string, $($arg),+)]
+            static SYMBOL: u8 = 0;
+
+            let mut f = $crate::Formatter::new($crate::id(&SYMBOL));
+            $($crate::Format::format(&$arg, &mut f);)+
+            (Some(f), Some(&mut $logger))
+        } else {
+            (None, None)
+        };
+
+        (&mut &mut $crate::dispatch::Logger::new(logger)).record_args(record)
+    }};
+
+    ($logger:expr, $string:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::dispatch::prelude::*;
+
+        let (id, logger) = if $crate::max_level() as u8 >= $crate::Level::Debug as u8 {
+            #[$crate::__message(".stlog.debug", $string)]
+            static SYMBOL: u8 = 0;
+
+            (Some($crate::id(&SYMBOL)), Some(&mut $logger))
+        } else {
+            (None, None)
+        };
+
+        (&mut &mut $crate::dispatch::Logger::new(logger)).record(id)
     }};
 
     ($string:expr) => {
@@ -389,14 +852,13 @@ macro_rules! debug {
             if $crate::max_level() as u8 >= $crate::Level::Debug as u8 {
                 extern "Rust" {
                     #[link_name = "stlog::GLOBAL_LOGGER"]
-                    static LOGGER: &'static $crate::GlobalLog;
+                    static LOGGER: &'static dyn $crate::GlobalLog;
                 }
 
-                #[export_name = $string]
-                #[link_section = ".stlog.debug"]
+                #[$crate::__message(".stlog.debug", $string)]
                 static SYMBOL: u8 = 0;
 
-                $crate::GlobalLog::log(LOGGER, &SYMBOL as *const u8 as usize as u8)
+                $crate::GlobalLog::log(LOGGER, $crate::id(&SYMBOL))
             }
         }
     };
@@ -407,16 +869,54 @@ macro_rules! debug {
 /// For more details see the [`error!`](macro.error.html) macro.
 #[macro_export]
 macro_rules! trace {
-    ($logger:expr, $string:expr) => {{
+    ($string:literal, $($arg:expr),+) => {
         if $crate::max_level() as u8 >= $crate::Level::Trace as u8 {
-            #[export_name = $string]
-            #[link_section = ".stlog.trace"]
+            extern "Rust" {
+                #[link_name = "stlog::GLOBAL_LOGGER"]
+                static LOGGER: &'static dyn $crate::GlobalLog;
+            }
+
+            #[$crate::__message(".stlog.trace", $string, $($arg),+)]
             static SYMBOL: u8 = 0;
 
-            $crate::Log::log(&mut $logger, &SYMBOL as *const u8 as usize as u8)
-        } else {
-            Ok(())
+            let mut f = $crate::Formatter::new($crate::id(&SYMBOL));
+            $($crate::Format::format(&$arg, &mut f);)+
+            $crate::global_log_formatted(unsafe { LOGGER }, &f)
         }
+    };
+
+    ($logger:expr, $string:expr, $($arg:expr),+) => {{
+        #[allow(unused_imports)]
+        use $crate::dispatch::prelude::*;
+
+        let (record, logger) = if $crate::max_level() as u8 >= $crate::Level::Trace as u8 {
+            #[$crate::__message(".stlog.trace", $string, $($arg),+)]
+            static SYMBOL: u8 = 0;
+
+            let mut f = $crate::Formatter::new($crate::id(&SYMBOL));
+            $($crate::Format::format(&$arg, &mut f);)+
+            (Some(f), Some(&mut $logger))
+        } else {
+            (None, None)
+        };
+
+        (&mut &mut $crate::dispatch::Logger::new(logger)).record_args(record)
+    }};
+
+    ($logger:expr, $string:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::dispatch::prelude::*;
+
+        let (id, logger) = if $crate::max_level() as u8 >= $crate::Level::Trace as u8 {
+            #[$crate::__message(".stlog.trace", $string)]
+            static SYMBOL: u8 = 0;
+
+            (Some($crate::id(&SYMBOL)), Some(&mut $logger))
+        } else {
+            (None, None)
+        };
+
+        (&mut &mut $crate::dispatch::Logger::new(logger)).record(id)
     }};
 
     ($string:expr) => {
@@ -424,29 +924,609 @@ macro_rules! trace {
             if $crate::max_level() as u8 >= $crate::Level::Trace as u8 {
                 extern "Rust" {
                     #[link_name = "stlog::GLOBAL_LOGGER"]
-                    static LOGGER: &'static $crate::GlobalLog;
+                    static LOGGER: &'static dyn $crate::GlobalLog;
                 }
 
-                #[export_name = $string]
-                #[link_section = ".stlog.trace"]
+                #[$crate::__message(".stlog.trace", $string)]
                 static SYMBOL: u8 = 0;
 
-                $crate::GlobalLog::log(LOGGER, &SYMBOL as *const u8 as usize as u8)
+                $crate::GlobalLog::log(LOGGER, $crate::id(&SYMBOL))
             }
         }
     };
 }
 
+/// Logs the given string literal at the ERROR log level, but only the first time this line runs
+///
+/// `$logger` must be an expression whose type implements the [`Log`](trait.Log.html) trait.
+///
+/// If `$logger` is omitted the global logger will be used.
+#[macro_export]
+macro_rules! error_once {
+    ($logger:expr, $string:expr) => {
+        $crate::__throttled!(".stlog.error", Error, once, $logger, $string)
+    };
+
+    ($string:expr) => {
+        $crate::__throttled!(".stlog.error", Error, once, $string)
+    };
+}
+
+/// Logs the given string literal at the ERROR log level the first time this line runs and then
+/// once every `$n` times
+///
+/// `$n` must be a `u32` value. If `$n` is 0 or 1 the message is logged every time this line runs.
+///
+/// `$logger` must be an expression whose type implements the [`Log`](trait.Log.html) trait.
+///
+/// If `$logger` is omitted the global logger will be used.
+#[macro_export]
+macro_rules! error_every_n {
+    ($logger:expr, $n:expr, $string:expr) => {
+        $crate::__throttled!(".stlog.error", Error, every_n, $logger, $n, $string)
+    };
+
+    ($n:expr, $string:expr) => {
+        $crate::__throttled!(".stlog.error", Error, every_n, $n, $string)
+    };
+}
+
+/// Logs the given string literal at the ERROR log level, at most once every `$ticks` ticks
+///
+/// `$now` is the current time in ticks, e.g. the value returned by a
+/// [`Timestamp`](trait.Timestamp.html) implementation; both must be `u32` values.
+///
+/// When the message is logged again after having been suppressed, it's followed by an internal
+/// "suppressed N times" message that the decoder displays along with the message.
+///
+/// `$logger` must be an expression whose type implements the [`Log`](trait.Log.html) trait.
+///
+/// If `$logger` is omitted the global logger will be used.
+///
+/// # Example
+///
+/// ```
+/// use stlog::{error_throttled, Log};
+///
+/// struct Logger {
+///     bytes: usize,
+/// }
+///
+/// impl Log for Logger {
+///     type Error = ();
+///
+///     fn log(&mut self, _: u8) -> Result<(), ()> {
+///         self.bytes += 1;
+///         Ok(())
+///     }
+/// }
+///
+/// fn on_overrun(logger: &mut Logger, now: u32) {
+///     error_throttled!(*logger, now, 1_000, "FIFO overrun").unwrap();
+/// }
+///
+/// let mut logger = Logger { bytes: 0 };
+///
+/// // an interrupt storm: 100 overruns within 10 ticks
+/// for now in 0..100 {
+///     on_overrun(&mut logger, now / 10);
+/// }
+///
+/// // only the first occurrence got logged
+/// assert_eq!(logger.bytes, 1);
+///
+/// // much later: the message is logged followed by "FIFO overrun suppressed 99 times"
+/// on_overrun(&mut logger, 5_000);
+/// assert!(logger.bytes > 2);
+/// ```
+#[macro_export]
+macro_rules! error_throttled {
+    ($logger:expr, $now:expr, $ticks:expr, $string:expr) => {
+        $crate::__throttled!(
+            ".stlog.error",
+            Error,
+            throttled,
+            $logger,
+            $now,
+            $ticks,
+            $string
+        )
+    };
+
+    ($now:expr, $ticks:expr, $string:expr) => {
+        $crate::__throttled!(".stlog.error", Error, throttled, $now, $ticks, $string)
+    };
+}
+
+/// Logs the given string literal at the WARNING log level, but only the first time this line runs
+///
+/// For more details see the [`error_once!`](macro.error_once.html) macro.
+#[macro_export]
+macro_rules! warn_once {
+    ($logger:expr, $string:expr) => {
+        $crate::__throttled!(".stlog.warn", Warn, once, $logger, $string)
+    };
+
+    ($string:expr) => {
+        $crate::__throttled!(".stlog.warn", Warn, once, $string)
+    };
+}
+
+/// Logs the given string literal at the WARNING log level the first time this line runs and then
+/// once every `$n` times
+///
+/// For more details see the [`error_every_n!`](macro.error_every_n.html) macro.
+#[macro_export]
+macro_rules! warn_every_n {
+    ($logger:expr, $n:expr, $string:expr) => {
+        $crate::__throttled!(".stlog.warn", Warn, every_n, $logger, $n, $string)
+    };
+
+    ($n:expr, $string:expr) => {
+        $crate::__throttled!(".stlog.warn", Warn, every_n, $n, $string)
+    };
+}
+
+/// Logs the given string literal at the WARNING log level, at most once every `$ticks` ticks
+///
+/// For more details see the [`error_throttled!`](macro.error_throttled.html) macro.
+#[macro_export]
+macro_rules! warn_throttled {
+    ($logger:expr, $now:expr, $ticks:expr, $string:expr) => {
+        $crate::__throttled!(
+            ".stlog.warn",
+            Warn,
+            throttled,
+            $logger,
+            $now,
+            $ticks,
+            $string
+        )
+    };
+
+    ($now:expr, $ticks:expr, $string:expr) => {
+        $crate::__throttled!(".stlog.warn", Warn, throttled, $now, $ticks, $string)
+    };
+}
+
+/// Logs the given string literal at the INFO log level, but only the first time this line runs
+///
+/// For more details see the [`error_once!`](macro.error_once.html) macro.
+#[macro_export]
+macro_rules! info_once {
+    ($logger:expr, $string:expr) => {
+        $crate::__throttled!(".stlog.info", Info, once, $logger, $string)
+    };
+
+    ($string:expr) => {
+        $crate::__throttled!(".stlog.info", Info, once, $string)
+    };
+}
+
+/// Logs the given string literal at the INFO log level the first time this line runs and then
+/// once every `$n` times
+///
+/// For more details see the [`error_every_n!`](macro.error_every_n.html) macro.
+#[macro_export]
+macro_rules! info_every_n {
+    ($logger:expr, $n:expr, $string:expr) => {
+        $crate::__throttled!(".stlog.info", Info, every_n, $logger, $n, $string)
+    };
+
+    ($n:expr, $string:expr) => {
+        $crate::__throttled!(".stlog.info", Info, every_n, $n, $string)
+    };
+}
+
+/// Logs the given string literal at the INFO log level, at most once every `$ticks` ticks
+///
+/// For more details see the [`error_throttled!`](macro.error_throttled.html) macro.
+#[macro_export]
+macro_rules! info_throttled {
+    ($logger:expr, $now:expr, $ticks:expr, $string:expr) => {
+        $crate::__throttled!(
+            ".stlog.info",
+            Info,
+            throttled,
+            $logger,
+            $now,
+            $ticks,
+            $string
+        )
+    };
+
+    ($now:expr, $ticks:expr, $string:expr) => {
+        $crate::__throttled!(".stlog.info", Info, throttled, $now, $ticks, $string)
+    };
+}
+
+/// Logs the given string literal at the DEBUG log level, but only the first time this line runs
+///
+/// For more details see the [`error_once!`](macro.error_once.html) macro.
+#[macro_export]
+macro_rules! debug_once {
+    ($logger:expr, $string:expr) => {
+        $crate::__throttled!(".stlog.debug", Debug, once, $logger, $string)
+    };
+
+    ($string:expr) => {
+        $crate::__throttled!(".stlog.debug", Debug, once, $string)
+    };
+}
+
+/// Logs the given string literal at the DEBUG log level the first time this line runs and then
+/// once every `$n` times
+///
+/// For more details see the [`error_every_n!`](macro.error_every_n.html) macro.
+#[macro_export]
+macro_rules! debug_every_n {
+    ($logger:expr, $n:expr, $string:expr) => {
+        $crate::__throttled!(".stlog.debug", Debug, every_n, $logger, $n, $string)
+    };
+
+    ($n:expr, $string:expr) => {
+        $crate::__throttled!(".stlog.debug", Debug, every_n, $n, $string)
+    };
+}
+
+/// Logs the given string literal at the DEBUG log level, at most once every `$ticks` ticks
+///
+/// For more details see the [`error_throttled!`](macro.error_throttled.html) macro.
+#[macro_export]
+macro_rules! debug_throttled {
+    ($logger:expr, $now:expr, $ticks:expr, $string:expr) => {
+        $crate::__throttled!(
+            ".stlog.debug",
+            Debug,
+            throttled,
+            $logger,
+            $now,
+            $ticks,
+            $string
+        )
+    };
+
+    ($now:expr, $ticks:expr, $string:expr) => {
+        $crate::__throttled!(".stlog.debug", Debug, throttled, $now, $ticks, $string)
+    };
+}
+
+/// Logs the given string literal at the TRACE log level, but only the first time this line runs
+///
+/// For more details see the [`error_once!`](macro.error_once.html) macro.
+#[macro_export]
+macro_rules! trace_once {
+    ($logger:expr, $string:expr) => {
+        $crate::__throttled!(".stlog.trace", Trace, once, $logger, $string)
+    };
+
+    ($string:expr) => {
+        $crate::__throttled!(".stlog.trace", Trace, once, $string)
+    };
+}
+
+/// Logs the given string literal at the TRACE log level the first time this line runs and then
+/// once every `$n` times
+///
+/// For more details see the [`error_every_n!`](macro.error_every_n.html) macro.
+#[macro_export]
+macro_rules! trace_every_n {
+    ($logger:expr, $n:expr, $string:expr) => {
+        $crate::__throttled!(".stlog.trace", Trace, every_n, $logger, $n, $string)
+    };
+
+    ($n:expr, $string:expr) => {
+        $crate::__throttled!(".stlog.trace", Trace, every_n, $n, $string)
+    };
+}
+
+/// Logs the given string literal at the TRACE log level, at most once every `$ticks` ticks
+///
+/// For more details see the [`error_throttled!`](macro.error_throttled.html) macro.
+#[macro_export]
+macro_rules! trace_throttled {
+    ($logger:expr, $now:expr, $ticks:expr, $string:expr) => {
+        $crate::__throttled!(
+            ".stlog.trace",
+            Trace,
+            throttled,
+            $logger,
+            $now,
+            $ticks,
+            $string
+        )
+    };
+
+    ($now:expr, $ticks:expr, $string:expr) => {
+        $crate::__throttled!(".stlog.trace", Trace, throttled, $now, $ticks, $string)
+    };
+}
+
+// NOTE the `*_once!`, `*_every_n!` and `*_throttled!` macros expand to this macro; `$section` and
+// `$level` select the log level
 #[doc(hidden)]
+#[macro_export]
+macro_rules! __throttled {
+    ($section:tt, $level:ident, once, $logger:expr, $string:expr) => {{
+        static ONCE: $crate::Once = $crate::Once::new();
+
+        $crate::__throttled!(@log $section, $level, ONCE.first(), $logger, $string)
+    }};
+
+    ($section:tt, $level:ident, once, $string:expr) => {{
+        static ONCE: $crate::Once = $crate::Once::new();
+
+        $crate::__throttled!(@log $section, $level, ONCE.first(), $string)
+    }};
+
+    ($section:tt, $level:ident, every_n, $logger:expr, $n:expr, $string:expr) => {{
+        static EVERY_N: $crate::EveryN = $crate::EveryN::new();
+
+        $crate::__throttled!(@log $section, $level, EVERY_N.tick($n), $logger, $string)
+    }};
+
+    ($section:tt, $level:ident, every_n, $n:expr, $string:expr) => {{
+        static EVERY_N: $crate::EveryN = $crate::EveryN::new();
+
+        $crate::__throttled!(@log $section, $level, EVERY_N.tick($n), $string)
+    }};
+
+    ($section:tt, $level:ident, throttled, $logger:expr, $now:expr, $ticks:expr, $string:expr) => {{
+        if $crate::max_level() as u8 >= $crate::Level::$level as u8 {
+            static THROTTLE: $crate::Throttle = $crate::Throttle::new();
+
+            if let Some(suppressed) = THROTTLE.check($now, $ticks) {
+                #[$crate::__message($section, $string)]
+                static SYMBOL: u8 = 0;
+
+                $crate::log_throttled(&mut $logger, $crate::id(&SYMBOL), suppressed)
+            } else {
+                Ok(())
+            }
+        } else {
+            Ok(())
+        }
+    }};
+
+    ($section:tt, $level:ident, throttled, $now:expr, $ticks:expr, $string:expr) => {{
+        if $crate::max_level() as u8 >= $crate::Level::$level as u8 {
+            static THROTTLE: $crate::Throttle = $crate::Throttle::new();
+
+            if let Some(suppressed) = THROTTLE.check($now, $ticks) {
+                extern "Rust" {
+                    #[link_name = "stlog::GLOBAL_LOGGER"]
+                    static LOGGER: &'static dyn $crate::GlobalLog;
+                }
+
+                #[$crate::__message($section, $string)]
+                static SYMBOL: u8 = 0;
+
+                $crate::global_log_throttled(unsafe { LOGGER }, $crate::id(&SYMBOL), suppressed)
+            }
+        }
+    }};
+
+    // logs the message if its level is enabled and `$allow` is true
+    (@log $section:tt, $level:ident, $allow:expr, $logger:expr, $string:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::dispatch::prelude::*;
+
+        let (id, logger) = if $crate::max_level() as u8 >= $crate::Level::$level as u8 && $allow {
+            #[$crate::__message($section, $string)]
+            static SYMBOL: u8 = 0;
+
+            (Some($crate::id(&SYMBOL)), Some(&mut $logger))
+        } else {
+            (None, None)
+        };
+
+        (&mut &mut $crate::dispatch::Logger::new(logger)).record(id)
+    }};
+
+    (@log $section:tt, $level:ident, $allow:expr, $string:expr) => {
+        if $crate::max_level() as u8 >= $crate::Level::$level as u8 && $allow {
+            extern "Rust" {
+                #[link_name = "stlog::GLOBAL_LOGGER"]
+                static LOGGER: &'static dyn $crate::GlobalLog;
+            }
+
+            #[$crate::__message($section, $string)]
+            static SYMBOL: u8 = 0;
+
+            $crate::GlobalLog::log(unsafe { LOGGER }, $crate::id(&SYMBOL))
+        }
+    };
+}
+
+/// Traces the execution of the enclosing scope
+///
+/// Logs an "enter" record right away and returns a [`Span`](struct.Span.html) guard that logs the
+/// matching "exit" record when dropped. The decoder pairs both records, indents the messages
+/// logged while the span is active and, if the records are timestamped, reports how long the span
+/// lasted.
+///
+/// The first argument is the path to one of the variants of [`Level`](enum.Level.html), e.g.
+/// `Level::Debug` or `stlog::Level::Debug`. Spans whose level is disabled log nothing.
+///
+/// Spans are always logged through the global logger: the guard must be able to log the exit
+/// record without holding on to a local logger. Like any other record sent to the global logger,
+/// the "enter" and "exit" records only get timestamped if the global logger calls
+/// [`Log::begin`](trait.Log.html#method.begin); see [`GlobalLog`](trait.GlobalLog.html).
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Mutex;
+///
+/// use stlog::{global_logger, info, span, GlobalLog, Level};
+///
+/// struct Logger {
+///     records: Mutex<Vec<Vec<u8>>>,
+/// }
+///
+/// impl GlobalLog for Logger {
+///     fn log(&self, address: u8) {
+///         self.log_bytes(&[address]);
+///     }
+///
+///     fn log_bytes(&self, bytes: &[u8]) {
+///         self.records.lock().unwrap().push(bytes.to_vec());
+///     }
+/// }
+///
+/// #[global_logger]
+/// static LOGGER: Logger = Logger {
+///     records: Mutex::new(Vec::new()),
+/// };
+///
+/// fn flash_erase() {
+///     let _span = span!(Level::Debug, "flash_erase");
+///
+///     info!("Erasing flash");
+///     for _sector in 0..2 {
+///         let _span = span!(stlog::Level::Debug, "erase_sector");
+///         // ..
+///     }
+/// } // <- the "exit" record is logged here
+///
+/// fn main() {
+///     flash_erase();
+///
+///     // "enter", "Erasing flash", 2 x ("enter", "exit") and "exit"
+///     assert_eq!(LOGGER.records.lock().unwrap().len(), 7);
+/// }
+/// ```
+///
+/// ``` text
+/// $ cat /dev/ttyUSB0 | stlog-decode -e /path/to/device/binary -t 32768
+///          0.000000 DEBUG -> flash_erase
+///          0.000030 INFO    Erasing flash
+///          0.048011 DEBUG <- flash_erase (48.011ms)
+/// ```
+#[macro_export]
+macro_rules! span {
+    (@[$($level:tt)*] Error, $string:expr) => {
+        $crate::span!(@$($level)*, ".stlog.error", $string)
+    };
+
+    (@[$($level:tt)*] Warn, $string:expr) => {
+        $crate::span!(@$($level)*, ".stlog.warn", $string)
+    };
+
+    (@[$($level:tt)*] Info, $string:expr) => {
+        $crate::span!(@$($level)*, ".stlog.info", $string)
+    };
+
+    (@[$($level:tt)*] Debug, $string:expr) => {
+        $crate::span!(@$($level)*, ".stlog.debug", $string)
+    };
+
+    (@[$($level:tt)*] Trace, $string:expr) => {
+        $crate::span!(@$($level)*, ".stlog.trace", $string)
+    };
+
+    // strips the path to the variant, e.g. `stlog::Level::`, to find out its section
+    (@[$($level:tt)*] $head:ident :: $($tail:ident)::+, $string:expr) => {
+        $crate::span!(@[$($level)*] $($tail)::+, $string)
+    };
+
+    (@$level:path, $section:tt, $string:expr) => {{
+        if $crate::max_level() as u8 >= $level as u8 {
+            #[$crate::__message($section, $string)]
+            static SYMBOL: u8 = 0;
+
+            $crate::Span::enter($crate::id(&SYMBOL))
+        } else {
+            $crate::Span::disabled()
+        }
+    }};
+
+    ($($level:ident)::+, $string:expr) => {
+        $crate::span!(@[$($level)::+] $($level)::+, $string)
+    };
+}
+
+/// Logging levels, in decreasing order of severity
 pub enum Level {
+    /// No message is logged
     Off = 0,
+    /// Error level
     Error = 1,
+    /// Warning level
     Warn = 2,
+    /// Info level
     Info = 3,
+    /// Debug level
     Debug = 4,
+    /// Trace level
     Trace = 5,
 }
 
+#[doc(hidden)]
+pub mod dispatch;
+
+// NOTE `#[derive(Intern)]` and `#[derive(Format)]` declare their symbols through this macro so
+// that they are checked with the edition of this crate, which doesn't require
+// `#[unsafe(export_name)]`
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __intern {
+    ($symbol:expr) => {{
+        #[export_name = $symbol]
+        #[link_section = ".stlog.intern"]
+        static SYMBOL: u8 = 0;
+
+        $crate::id(&SYMBOL)
+    }};
+}
+#[doc(hidden)]
+pub use crate::format::{global_log_formatted, Formatter};
+#[doc(hidden)]
+pub use crate::levels::Levels;
+#[doc(hidden)]
+pub use crate::throttle::{global_log_throttled, log_throttled, EveryN, Once, Throttle};
+
+/// Marks the start of the `.stlog` section; `stlog.x` places it before any other symbol
+#[link_section = ".stlog.start"]
+static START: [u8; 0] = [];
+
+/// Returns the ID of the message whose symbol is `symbol`
+///
+/// The ID is the offset of the symbol within the `.stlog` section. On bare metal targets the
+/// section is linked at address 0 so the offset is simply the address of the symbol. On hosted
+/// targets the executable may be relocated at load time so the offset is computed from the address
+/// of the start of the section.
+#[doc(hidden)]
+#[inline(always)]
+pub fn id(symbol: &'static u8) -> u8 {
+    let address = symbol as *const u8 as usize;
+
+    if cfg!(target_os = "none") {
+        address as u8
+    } else {
+        address.wrapping_sub(START.as_ptr() as usize) as u8
+    }
+}
+
+#[doc(hidden)]
+#[inline(always)]
+pub fn log_record<L>(logger: &mut L, address: u8) -> Result<(), L::Error>
+where
+    L: Log + ?Sized,
+{
+    logger.begin()?;
+    logger.log_bytes(&[address])
+}
+
+/// Sends the message at `address`, followed by its `args`, as a single record
+pub(crate) fn log_message<L>(logger: &mut L, address: u8, args: &[u32]) -> Result<(), L::Error>
+where
+    L: Log + ?Sized,
+{
+    logger.begin()?;
+    logger.log_bytes(Buffer::message(address, args).as_slice())
+}
+
 #[doc(hidden)]
 #[inline(always)]
 pub fn max_level() -> Level {
@@ -459,7 +1539,7 @@ pub fn max_level() -> Level {
             #[cfg(feature = "max-level-error")]
             return Level::Error;
 
-            #[cfg(feature = "max-level-warn")]
+            #[cfg(feature = "max-level-warning")]
             return Level::Warn;
 
             #[cfg(feature = "max-level-info")]
@@ -482,7 +1562,7 @@ pub fn max_level() -> Level {
             #[cfg(feature = "release-max-level-error")]
             return Level::Error;
 
-            #[cfg(feature = "release-max-level-warn")]
+            #[cfg(feature = "release-max-level-warning")]
             return Level::Warn;
 
             #[cfg(feature = "release-max-level-info")]
@@ -499,3 +1579,71 @@ pub fn max_level() -> Level {
         }
     }
 }
+
+/// Sends `n` through the `logger` using the unsigned LEB128 encoding
+fn uleb128<L>(logger: &mut L, mut n: u32) -> Result<(), L::Error>
+where
+    L: Log + ?Sized,
+{
+    loop {
+        let byte = (n & 0x7f) as u8;
+        n >>= 7;
+
+        if n == 0 {
+            return logger.log(byte);
+        }
+
+        logger.log(byte | 0x80)?;
+    }
+}
+
+/// A record built on the stack
+pub(crate) struct Buffer {
+    bytes: [u8; 16],
+    len: usize,
+}
+
+impl Buffer {
+    pub(crate) const fn new() -> Self {
+        Buffer {
+            bytes: [0; 16],
+            len: 0,
+        }
+    }
+
+    /// Builds the record of the message at `address` followed by its `args`
+    pub(crate) fn message(address: u8, args: &[u32]) -> Self {
+        let mut record = Buffer::new();
+        Log::log(&mut record, address).ok();
+        for arg in args {
+            // NOTE the largest internal message, an address and two arguments, fits in the buffer
+            uleb128(&mut record, *arg).ok();
+        }
+        record
+    }
+
+    /// Appends `bytes` to the record
+    pub(crate) fn extend(&mut self, bytes: &[u8]) -> Result<(), ()> {
+        let end = self.len + bytes.len();
+        self.bytes
+            .get_mut(self.len..end)
+            .ok_or(())?
+            .copy_from_slice(bytes);
+        self.len = end;
+        Ok(())
+    }
+
+    pub(crate) fn as_slice(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+impl Log for Buffer {
+    type Error = ();
+
+    fn log(&mut self, byte: u8) -> Result<(), ()> {
+        *self.bytes.get_mut(self.len).ok_or(())? = byte;
+        self.len += 1;
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+//! A global logger that's initialized at runtime
+
+use core::{
+    cell::UnsafeCell,
+    mem::MaybeUninit,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::GlobalLog;
+
+/// A global logger whose inner logger is initialized at runtime, e.g. after the UART it writes to
+/// has been configured
+///
+/// Messages logged before [`set`](struct.OnceLogger.html#method.set) is called are discarded.
+/// Logging is lock-free; only `set` uses a `critical-section` so the application must provide a
+/// `critical-section` implementation.
+///
+/// # Example
+///
+/// ```
+/// use stlog::{global_logger, info, GlobalLog, OnceLogger};
+///
+/// struct Uart {
+///     // ..
+/// #   baud_rate: u32,
+/// }
+///
+/// impl GlobalLog for Uart {
+///     // ..
+/// #   fn log(&self, _: u8) {}
+/// }
+///
+/// #[global_logger]
+/// static LOGGER: OnceLogger<Uart> = OnceLogger::new();
+///
+/// fn main() {
+///     info!("Discarded");
+///
+///     let uart = Uart { baud_rate: 115_200 };
+///     assert!(LOGGER.set(uart).is_ok());
+///
+///     info!("Hello!");
+/// }
+/// ```
+pub struct OnceLogger<L> {
+    ready: AtomicBool,
+    logger: UnsafeCell<MaybeUninit<L>>,
+}
+
+unsafe impl<L> Sync for OnceLogger<L> where L: Send + Sync {}
+
+impl<L> OnceLogger<L> {
+    /// Creates an uninitialized logger
+    pub const fn new() -> Self {
+        OnceLogger {
+            ready: AtomicBool::new(false),
+            logger: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    /// Initializes the logger
+    ///
+    /// Returns `Err(logger)` if the logger has already been initialized.
+    pub fn set(&self, logger: L) -> Result<(), L> {
+        critical_section::with(|_| {
+            if self.ready.load(Ordering::Relaxed) {
+                Err(logger)
+            } else {
+                unsafe { (*self.logger.get()).as_mut_ptr().write(logger) }
+                self.ready.store(true, Ordering::Release);
+                Ok(())
+            }
+        })
+    }
+
+    /// Returns the inner logger, if it has been initialized
+    pub fn get(&self) -> Option<&L> {
+        if self.ready.load(Ordering::Acquire) {
+            Some(unsafe { &*(*self.logger.get()).as_ptr() })
+        } else {
+            None
+        }
+    }
+}
+
+impl<L> GlobalLog for OnceLogger<L>
+where
+    L: GlobalLog + Send,
+{
+    fn log(&self, byte: u8) {
+        if let Some(logger) = self.get() {
+            logger.log(byte)
+        }
+    }
+
+    fn log_bytes(&self, bytes: &[u8]) {
+        if let Some(logger) = self.get() {
+            logger.log_bytes(bytes)
+        }
+    }
+}
+
+impl<L> Default for OnceLogger<L> {
+    fn default() -> Self {
+        OnceLogger::new()
+    }
+}
+
+impl<L> Drop for OnceLogger<L> {
+    fn drop(&mut self) {
+        if *self.ready.get_mut() {
+            unsafe { (*self.logger.get()).as_mut_ptr().drop_in_place() }
+        }
+    }
+}
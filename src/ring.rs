@@ -0,0 +1,106 @@
+//! A global logger that keeps the most recent records in RAM
+
+use core::{
+    cell::UnsafeCell,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::GlobalLog;
+
+/// Marks a valid ring buffer header; ASCII "STLG"
+const MAGIC: u32 = 0x5354_4c47;
+
+/// Address of the ring buffer that logged last; the decoder finds the buffer through this symbol
+#[export_name = "stlog::RING_BUFFER"]
+static RING_BUFFER: AtomicUsize = AtomicUsize::new(0);
+
+/// A global logger that keeps the last `N` records in a RAM buffer
+///
+/// Nothing is sent anywhere; the records stay in RAM where a debugger can retrieve them. This is
+/// most useful after a crash: dump the RAM (or create a core file) and run `stlog-decode -e
+/// <elf> -m <dump>`. The decoder finds the buffer through the `stlog::RING_BUFFER` symbol, which
+/// holds the address of the ring buffer that logged last, and prints the records in the order they
+/// were logged. This works whether the ring buffer is the `global_logger` or is reached through
+/// another logger, like a [`RuntimeLogger`](struct.RuntimeLogger.html).
+///
+/// This logger uses a `critical-section` to synchronize access to the buffer so the application
+/// must provide a `critical-section` implementation.
+///
+/// # Example
+///
+/// ```
+/// use stlog::{global_logger, info, RingBuffer};
+///
+/// #[global_logger]
+/// static LOGGER: RingBuffer<1024> = RingBuffer::new();
+///
+/// fn main() {
+///     info!("Hello!");
+/// }
+/// ```
+// NOTE the layout of this struct is read by the decoder; don't change it without updating
+// `stlog-decoder`
+#[repr(C)]
+pub struct RingBuffer<const N: usize> {
+    magic: u32,
+    size: u32,
+    /// Index of the next byte that will be written
+    write: UnsafeCell<u32>,
+    /// Number of valid bytes in the buffer
+    len: UnsafeCell<u32>,
+    buffer: UnsafeCell<[u8; N]>,
+}
+
+unsafe impl<const N: usize> Sync for RingBuffer<N> {}
+
+impl<const N: usize> RingBuffer<N> {
+    const NOT_EMPTY: () = assert!(N > 0, "the capacity of a `RingBuffer` can't be 0");
+
+    /// Creates an empty ring buffer
+    pub const fn new() -> Self {
+        let () = Self::NOT_EMPTY;
+
+        RingBuffer {
+            magic: MAGIC,
+            size: N as u32,
+            write: UnsafeCell::new(0),
+            len: UnsafeCell::new(0),
+            buffer: UnsafeCell::new([0; N]),
+        }
+    }
+
+    /// NOTE must be called from within a critical section
+    unsafe fn push(&self, byte: u8) {
+        let write = *self.write.get() as usize;
+        (*self.buffer.get())[write] = byte;
+
+        *self.write.get() = ((write + 1) % N) as u32;
+        if (*self.len.get() as usize) < N {
+            *self.len.get() += 1;
+        }
+    }
+}
+
+impl<const N: usize> GlobalLog for RingBuffer<N> {
+    fn log(&self, byte: u8) {
+        critical_section::with(|_| {
+            RING_BUFFER.store(self as *const Self as usize, Ordering::Relaxed);
+            unsafe { self.push(byte) }
+        })
+    }
+
+    fn log_bytes(&self, bytes: &[u8]) {
+        critical_section::with(|_| {
+            RING_BUFFER.store(self as *const Self as usize, Ordering::Relaxed);
+            for byte in bytes {
+                unsafe { self.push(*byte) }
+            }
+        })
+    }
+}
+
+impl<const N: usize> Default for RingBuffer<N> {
+    fn default() -> Self {
+        RingBuffer::new()
+    }
+}
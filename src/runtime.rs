@@ -0,0 +1,83 @@
+//! Global logger selected at runtime
+
+use core::cell::Cell;
+
+use critical_section::Mutex;
+
+use crate::GlobalLog;
+
+static LOGGER: Mutex<Cell<Option<&'static dyn GlobalLog>>> = Mutex::new(Cell::new(None));
+
+/// A global logger that forwards all messages to the logger set with
+/// [`set_global_logger`](fn.set_global_logger.html)
+///
+/// Messages logged before `set_global_logger` is called are discarded. The current logger is read
+/// within a `critical-section`, and then called outside of it, so the application must provide a
+/// `critical-section` implementation.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// use stlog::{global_logger, info, GlobalLog, RuntimeLogger};
+///
+/// #[global_logger]
+/// static LOGGER: RuntimeLogger = RuntimeLogger;
+///
+/// struct Counter(AtomicUsize);
+///
+/// impl GlobalLog for Counter {
+///     fn log(&self, _: u8) {
+///         self.0.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// static UART: Counter = Counter(AtomicUsize::new(0));
+/// static USB: Counter = Counter(AtomicUsize::new(0));
+///
+/// fn main() {
+///     info!("Discarded");
+///
+///     // the bootloader logs through the UART ..
+///     stlog::set_global_logger(&UART);
+///     info!("Booting");
+///
+///     // .. and then hands over to the USB logger
+///     stlog::set_global_logger(&USB);
+///     info!("Booted");
+///
+///     assert_eq!(UART.0.load(Ordering::Relaxed), 1);
+///     assert_eq!(USB.0.load(Ordering::Relaxed), 1);
+/// }
+/// ```
+pub struct RuntimeLogger;
+
+impl RuntimeLogger {
+    fn get() -> Option<&'static dyn GlobalLog> {
+        critical_section::with(|cs| LOGGER.borrow(cs).get())
+    }
+}
+
+impl GlobalLog for RuntimeLogger {
+    fn log(&self, address: u8) {
+        if let Some(logger) = RuntimeLogger::get() {
+            logger.log(address)
+        }
+    }
+
+    fn log_bytes(&self, bytes: &[u8]) {
+        if let Some(logger) = RuntimeLogger::get() {
+            logger.log_bytes(bytes)
+        }
+    }
+}
+
+/// Sets the logger that the [`RuntimeLogger`](struct.RuntimeLogger.html) forwards messages to
+///
+/// This function can be called several times, e.g. to hand over from the logger used during
+/// early boot to one that needs more initialization. It has no effect unless `RuntimeLogger` is
+/// the `#[global_logger]`.
+pub fn set_global_logger(logger: &'static dyn GlobalLog) {
+    critical_section::with(|cs| LOGGER.borrow(cs).set(Some(logger)))
+}
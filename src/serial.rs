@@ -0,0 +1,111 @@
+//! Loggers that write to a serial interface
+//!
+//! - [`hal`](hal/index.html), behind the `embedded-hal` Cargo feature, provides loggers for
+//!   `embedded_hal::serial::Write<u8>` (v0.2) implementers.
+//!
+//! - [`io`](io/index.html), behind the `embedded-io` Cargo feature, provides loggers for
+//!   `embedded_io::Write` (v0.6) implementers.
+//!
+//! Both modules provide a `SerialLogger`, which blocks until each byte has been written, and a
+//! `BufferedSerialLogger`, which never blocks: the bytes that can't be written right away are
+//! queued in a buffer that's drained by later calls to `log` or by explicit calls to `poll`.
+//!
+//! A buffered logger only accepts a new record if its buffer has room for the whole record, and
+//! for at least 16 bytes, so records are never split. When the buffer is full the record is
+//! discarded and [`Error::BufferFull`](enum.Error.html#variant.BufferFull) is returned; use a
+//! [`Dropped`](../struct.Dropped.html) counter to report these records to the decoder.
+
+#[cfg(feature = "embedded-hal")]
+pub mod hal;
+#[cfg(feature = "embedded-io")]
+pub mod io;
+
+/// Free space, in bytes, that the buffered loggers require to start a record: a timestamp plus a
+/// message ID and two arguments
+///
+/// Longer records, like the ones with `Format` arguments, are checked against the free space when
+/// they're handed over, as a whole, to `log_bytes`.
+const MAX_RECORD_LEN: usize = 16;
+
+/// Error returned by the buffered loggers
+#[derive(Debug, Eq, PartialEq)]
+pub enum Error<E> {
+    /// The serial interface reported an error
+    Serial(E),
+    /// The buffer has no room for another record; the record was discarded
+    BufferFull,
+}
+
+/// A FIFO of bytes that have yet to be written
+struct Queue<const N: usize> {
+    bytes: [u8; N],
+    /// Index of the oldest byte
+    read: usize,
+    len: usize,
+}
+
+impl<const N: usize> Queue<N> {
+    const BIG_ENOUGH: () = assert!(
+        N >= MAX_RECORD_LEN,
+        "the buffer must be able to hold at least one record"
+    );
+
+    fn new() -> Self {
+        let () = Self::BIG_ENOUGH;
+
+        Queue {
+            bytes: [0; N],
+            read: 0,
+            len: 0,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn has_room_for_record(&self) -> bool {
+        N - self.len >= MAX_RECORD_LEN
+    }
+
+    /// Appends all the `bytes`, or none of them if they don't fit
+    fn extend(&mut self, bytes: &[u8]) -> Result<(), ()> {
+        if N - self.len < bytes.len() {
+            return Err(());
+        }
+
+        for byte in bytes {
+            self.bytes[(self.read + self.len) % N] = *byte;
+            self.len += 1;
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "embedded-hal"), allow(dead_code))]
+    fn peek(&self) -> Option<u8> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.bytes[self.read])
+        }
+    }
+
+    /// Returns the oldest bytes that are stored contiguously in memory
+    #[cfg_attr(not(feature = "embedded-io"), allow(dead_code))]
+    fn contiguous(&self) -> &[u8] {
+        let end = if self.read + self.len > N {
+            N
+        } else {
+            self.read + self.len
+        };
+
+        &self.bytes[self.read..end]
+    }
+
+    /// Removes the `n` oldest bytes
+    fn consume(&mut self, n: usize) {
+        self.read = (self.read + n) % N;
+        self.len -= n;
+    }
+}
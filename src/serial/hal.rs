@@ -0,0 +1,143 @@
+//! Loggers for `embedded_hal::serial::Write<u8>` implementers
+
+use embedded_hal_02::serial::Write;
+
+use super::{Error, Queue};
+use crate::Log;
+
+/// A logger that blocks until each byte has been written to the serial interface
+///
+/// # Example
+///
+/// ``` ignore
+/// use stlog::{info, serial::hal::SerialLogger};
+///
+/// let tx = /* some `embedded_hal::serial::Write<u8>` implementer */;
+/// let mut logger = SerialLogger::new(tx);
+///
+/// info!(logger, "Hello, world!").unwrap();
+/// ```
+pub struct SerialLogger<W> {
+    serial: W,
+}
+
+impl<W> SerialLogger<W>
+where
+    W: Write<u8>,
+{
+    /// Creates a logger that writes to the given `serial` interface
+    pub fn new(serial: W) -> Self {
+        SerialLogger { serial }
+    }
+
+    /// Releases the serial interface
+    pub fn free(self) -> W {
+        self.serial
+    }
+}
+
+impl<W> Log for SerialLogger<W>
+where
+    W: Write<u8>,
+{
+    type Error = W::Error;
+
+    fn log(&mut self, byte: u8) -> Result<(), W::Error> {
+        nb::block!(self.serial.write(byte))
+    }
+}
+
+/// A logger that queues the bytes the serial interface can't accept right away
+///
+/// `N` is the size of the buffer, in bytes; it must be at least 16 bytes, otherwise the program
+/// doesn't compile. Call [`poll`](struct.BufferedSerialLogger.html#method.poll), e.g. from the
+/// serial interrupt handler or from the idle loop, to drain the buffer.
+///
+/// ``` compile_fail
+/// use stlog::serial::hal::BufferedSerialLogger;
+///
+/// struct Serial;
+///
+/// impl embedded_hal_02::serial::Write<u8> for Serial {
+///     // ..
+/// #   type Error = ();
+/// #
+/// #   fn write(&mut self, _: u8) -> nb::Result<(), ()> {
+/// #       Ok(())
+/// #   }
+/// #
+/// #   fn flush(&mut self) -> nb::Result<(), ()> {
+/// #       Ok(())
+/// #   }
+/// }
+///
+/// // error: the buffer must be able to hold at least one record
+/// let logger = BufferedSerialLogger::<_, 8>::new(Serial);
+/// ```
+pub struct BufferedSerialLogger<W, const N: usize> {
+    serial: W,
+    queue: Queue<N>,
+}
+
+impl<W, const N: usize> BufferedSerialLogger<W, N>
+where
+    W: Write<u8>,
+{
+    /// Creates a logger that writes to the given `serial` interface
+    pub fn new(serial: W) -> Self {
+        BufferedSerialLogger {
+            serial,
+            queue: Queue::new(),
+        }
+    }
+
+    /// Writes as many buffered bytes as the serial interface accepts without blocking
+    pub fn poll(&mut self) -> Result<(), W::Error> {
+        while let Some(byte) = self.queue.peek() {
+            match self.serial.write(byte) {
+                Ok(()) => self.queue.consume(1),
+                Err(nb::Error::WouldBlock) => break,
+                Err(nb::Error::Other(e)) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` if all the logged bytes have been handed to the serial interface
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Releases the serial interface, discarding the buffered bytes
+    pub fn free(self) -> W {
+        self.serial
+    }
+}
+
+impl<W, const N: usize> Log for BufferedSerialLogger<W, N>
+where
+    W: Write<u8>,
+{
+    type Error = Error<W::Error>;
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        self.poll().map_err(Error::Serial)?;
+
+        if self.queue.has_room_for_record() {
+            Ok(())
+        } else {
+            Err(Error::BufferFull)
+        }
+    }
+
+    fn log(&mut self, byte: u8) -> Result<(), Self::Error> {
+        self.log_bytes(&[byte])
+    }
+
+    fn log_bytes(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        // NOTE the record is discarded as a whole if it doesn't fit
+        self.queue.extend(bytes).map_err(|_| Error::BufferFull)?;
+        self.poll().map_err(Error::Serial)
+    }
+}
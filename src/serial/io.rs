@@ -0,0 +1,129 @@
+//! Loggers for `embedded_io::Write` implementers
+
+use embedded_io_06::{Write, WriteReady};
+
+use super::{Error, Queue};
+use crate::Log;
+
+/// A logger that blocks until each byte has been written
+///
+/// # Example
+///
+/// ``` ignore
+/// use stlog::{info, serial::io::SerialLogger};
+///
+/// let tx = /* some `embedded_io::Write` implementer */;
+/// let mut logger = SerialLogger::new(tx);
+///
+/// info!(logger, "Hello, world!").unwrap();
+/// ```
+pub struct SerialLogger<W> {
+    writer: W,
+}
+
+impl<W> SerialLogger<W>
+where
+    W: Write,
+{
+    /// Creates a logger that writes to the given `writer`
+    pub fn new(writer: W) -> Self {
+        SerialLogger { writer }
+    }
+
+    /// Releases the writer
+    pub fn free(self) -> W {
+        self.writer
+    }
+}
+
+impl<W> Log for SerialLogger<W>
+where
+    W: Write,
+{
+    type Error = W::Error;
+
+    fn log(&mut self, byte: u8) -> Result<(), W::Error> {
+        self.writer.write_all(&[byte])
+    }
+
+    fn log_bytes(&mut self, bytes: &[u8]) -> Result<(), W::Error> {
+        self.writer.write_all(bytes)
+    }
+}
+
+/// A logger that queues the bytes the writer can't accept right away
+///
+/// The writer must implement `WriteReady`; bytes are only written when `write_ready` returns
+/// `true`.
+///
+/// `N` is the size of the buffer, in bytes; it must be at least 16 bytes, otherwise the program
+/// doesn't compile. Call [`poll`](struct.BufferedSerialLogger.html#method.poll), e.g. from the
+/// serial interrupt handler or from the idle loop, to drain the buffer.
+pub struct BufferedSerialLogger<W, const N: usize> {
+    writer: W,
+    queue: Queue<N>,
+}
+
+impl<W, const N: usize> BufferedSerialLogger<W, N>
+where
+    W: Write + WriteReady,
+{
+    /// Creates a logger that writes to the given `writer`
+    pub fn new(writer: W) -> Self {
+        BufferedSerialLogger {
+            writer,
+            queue: Queue::new(),
+        }
+    }
+
+    /// Writes as many buffered bytes as the writer accepts without blocking
+    pub fn poll(&mut self) -> Result<(), W::Error> {
+        while !self.queue.is_empty() && self.writer.write_ready()? {
+            let n = self.writer.write(self.queue.contiguous())?;
+            if n == 0 {
+                break;
+            }
+
+            self.queue.consume(n);
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` if all the logged bytes have been handed to the writer
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Releases the writer, discarding the buffered bytes
+    pub fn free(self) -> W {
+        self.writer
+    }
+}
+
+impl<W, const N: usize> Log for BufferedSerialLogger<W, N>
+where
+    W: Write + WriteReady,
+{
+    type Error = Error<W::Error>;
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        self.poll().map_err(Error::Serial)?;
+
+        if self.queue.has_room_for_record() {
+            Ok(())
+        } else {
+            Err(Error::BufferFull)
+        }
+    }
+
+    fn log(&mut self, byte: u8) -> Result<(), Self::Error> {
+        self.log_bytes(&[byte])
+    }
+
+    fn log_bytes(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        // NOTE the record is discarded as a whole if it doesn't fit
+        self.queue.extend(bytes).map_err(|_| Error::BufferFull)?;
+        self.poll().map_err(Error::Serial)
+    }
+}
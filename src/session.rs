@@ -0,0 +1,263 @@
+//! Firmware identity handshake
+
+use crate::{log_message, Log};
+
+#[export_name = "session start {}"]
+#[link_section = ".stlog.internal"]
+static SESSION: u8 = 0;
+
+/// Type of the ELF note that contains the GNU build ID
+const NT_GNU_BUILD_ID: u32 = 3;
+
+/// Sends a "session start" record that identifies the firmware build
+///
+/// The record carries the build ID of the firmware: the first 4 bytes of the GNU build ID, a hash
+/// that the linker computes over the whole executable, `.stlog` symbols included. The build ID is
+/// reproducible, it only changes when the program does. The decoder compares it against the build
+/// ID of the ELF file it was given and refuses to decode the stream if they don't match.
+///
+/// On Linux the build ID is found through the program headers of the executable; if the program
+/// has none, 0 is sent and the decoder skips the check. On other OSes 0 is always sent.
+///
+/// On targets without an OS (`target_os = "none"`) `stlog.x` points the `__stlog_build_id` symbol
+/// at the `.note.gnu.build-id` section, so the build ID is required: the linker must be told to
+/// emit it, e.g. with `-C link-arg=--build-id`, and the section must be placed in flash. Otherwise
+/// linking fails; see [The firmware has no build ID](index.html#the-firmware-has-no-build-id).
+///
+/// This should be called once at boot, before any other message is logged. When using a global
+/// logger call this on the underlying [`Log`](trait.Log.html) implementer before enabling the
+/// interrupts that may log. [`Session`](struct.Session.html) calls this before the first record.
+///
+/// # Example
+///
+/// ```
+/// use stlog::{info, Log};
+///
+/// struct Logger;
+///
+/// impl Log for Logger {
+///     // ..
+/// #   type Error = ();
+/// #
+/// #   fn log(&mut self, _: u8) -> Result<(), ()> {
+/// #       Ok(())
+/// #   }
+/// }
+///
+/// let mut logger = Logger;
+///
+/// stlog::start_session(&mut logger).unwrap();
+///
+/// info!(logger, "Hello, world!").unwrap();
+/// ```
+pub fn start_session<L>(logger: &mut L) -> Result<(), L::Error>
+where
+    L: Log + ?Sized,
+{
+    log_message(logger, crate::id(&SESSION), &[build_id().unwrap_or(0)])
+}
+
+/// A logger that sends a "session start" record before its first record
+///
+/// See [`start_session`](fn.start_session.html). This should be the outermost wrapper; that way
+/// the "session start" record goes through the other wrappers, e.g. it gets timestamped by
+/// [`Timestamped`](struct.Timestamped.html).
+///
+/// # Example
+///
+/// ```
+/// use stlog::{info, Log, Session};
+///
+/// #[derive(Default)]
+/// struct Uart {
+///     sent: Vec<u8>,
+/// }
+///
+/// impl Log for Uart {
+///     type Error = ();
+///
+///     fn log(&mut self, byte: u8) -> Result<(), ()> {
+///         self.sent.push(byte);
+///         Ok(())
+///     }
+/// }
+///
+/// let mut logger = Session::new(Uart::default());
+///
+/// info!(logger, "Hello").unwrap();
+/// info!(logger, "World").unwrap();
+///
+/// let mut session = Uart::default();
+/// stlog::start_session(&mut session).unwrap();
+///
+/// let uart = logger.free();
+/// assert!(uart.sent.starts_with(&session.sent));
+/// assert_eq!(uart.sent.len(), session.sent.len() + 2);
+/// ```
+pub struct Session<L> {
+    logger: L,
+    started: bool,
+}
+
+impl<L> Session<L> {
+    /// Wraps the given `logger`
+    pub const fn new(logger: L) -> Self {
+        Session {
+            logger,
+            started: false,
+        }
+    }
+
+    /// Releases the logger
+    pub fn free(self) -> L {
+        self.logger
+    }
+}
+
+impl<L> Session<L>
+where
+    L: Log,
+{
+    fn start(&mut self) -> Result<(), L::Error> {
+        if !self.started {
+            start_session(&mut self.logger)?;
+            self.started = true;
+        }
+
+        Ok(())
+    }
+}
+
+impl<L> Log for Session<L>
+where
+    L: Log,
+{
+    type Error = L::Error;
+
+    fn log(&mut self, byte: u8) -> Result<(), L::Error> {
+        self.start()?;
+        self.logger.log(byte)
+    }
+
+    fn begin(&mut self) -> Result<(), L::Error> {
+        self.start()?;
+        self.logger.begin()
+    }
+
+    fn log_bytes(&mut self, bytes: &[u8]) -> Result<(), L::Error> {
+        self.start()?;
+        self.logger.log_bytes(bytes)
+    }
+}
+
+/// Returns the first 4 bytes of the GNU build ID of the program
+#[cfg(target_os = "linux")]
+fn build_id() -> Option<u32> {
+    use core::slice;
+
+    extern "C" {
+        // the ELF header of the program; defined by the linker
+        static __ehdr_start: u8;
+    }
+
+    const PT_LOAD: u32 = 1;
+    const PT_NOTE: u32 = 4;
+
+    unsafe {
+        let ehdr = &__ehdr_start as *const u8 as usize;
+        let phoff = read::<usize>(ehdr + elf::E_PHOFF);
+        let phentsize = usize::from(read::<u16>(ehdr + elf::E_PHENTSIZE));
+        let phnum = usize::from(read::<u16>(ehdr + elf::E_PHNUM));
+        let phdrs = (0..phnum).map(|i| ehdr + phoff + i * phentsize);
+
+        // the ELF header is at the start of the segment whose file offset is 0; this gives the
+        // address at which the program was loaded
+        let vaddr = phdrs
+            .clone()
+            .find(|ph| read::<u32>(*ph) == PT_LOAD && read::<usize>(ph + elf::P_OFFSET) == 0)
+            .map(|ph| read::<usize>(ph + elf::P_VADDR))?;
+        let bias = ehdr.wrapping_sub(vaddr);
+
+        phdrs
+            .filter(|ph| read::<u32>(*ph) == PT_NOTE)
+            .find_map(|ph| {
+                let start = bias.wrapping_add(read::<usize>(ph + elf::P_VADDR));
+                let len = read::<usize>(ph + elf::P_FILESZ);
+                find_build_id(slice::from_raw_parts(start as *const u8, len))
+            })
+    }
+}
+
+/// Returns the first 4 bytes of the GNU build ID of the program
+#[cfg(target_os = "none")]
+fn build_id() -> Option<u32> {
+    use core::slice;
+
+    extern "C" {
+        // the `.note.gnu.build-id` section; see `stlog.x`
+        static __stlog_build_id: u8;
+    }
+
+    unsafe {
+        let note = &__stlog_build_id as *const u8 as usize;
+        let len = 12 + align4(read::<u32>(note) as usize) + align4(read::<u32>(note + 4) as usize);
+        find_build_id(slice::from_raw_parts(note as *const u8, len))
+    }
+}
+
+/// Returns the first 4 bytes of the GNU build ID of the program
+#[cfg(not(any(target_os = "linux", target_os = "none")))]
+fn build_id() -> Option<u32> {
+    None
+}
+
+/// Offsets of the fields of the ELF header and of the program headers
+#[cfg(all(target_os = "linux", target_pointer_width = "64"))]
+mod elf {
+    pub const E_PHOFF: usize = 0x20;
+    pub const E_PHENTSIZE: usize = 0x36;
+    pub const E_PHNUM: usize = 0x38;
+    pub const P_OFFSET: usize = 0x08;
+    pub const P_VADDR: usize = 0x10;
+    pub const P_FILESZ: usize = 0x20;
+}
+
+/// Offsets of the fields of the ELF header and of the program headers
+#[cfg(all(target_os = "linux", target_pointer_width = "32"))]
+mod elf {
+    pub const E_PHOFF: usize = 0x1c;
+    pub const E_PHENTSIZE: usize = 0x2a;
+    pub const E_PHNUM: usize = 0x2c;
+    pub const P_OFFSET: usize = 0x04;
+    pub const P_VADDR: usize = 0x08;
+    pub const P_FILESZ: usize = 0x10;
+}
+
+#[cfg(any(target_os = "linux", target_os = "none"))]
+unsafe fn read<T>(address: usize) -> T {
+    core::ptr::read_unaligned(address as *const T)
+}
+
+/// Looks for the GNU build ID in a sequence of ELF notes
+#[cfg(any(target_os = "linux", target_os = "none"))]
+fn find_build_id(mut notes: &[u8]) -> Option<u32> {
+    while notes.len() >= 12 {
+        let word = |i: usize| unsafe { read::<u32>(notes.as_ptr() as usize + 4 * i) };
+        let (namesz, descsz, kind) = (word(0) as usize, word(1) as usize, word(2));
+
+        let desc = 12 + align4(namesz);
+        if kind == NT_GNU_BUILD_ID && notes.get(12..16) == Some(&b"GNU\0"[..]) && descsz >= 4 {
+            let id = notes.get(desc..desc + 4)?;
+            return Some(u32::from_le_bytes([id[0], id[1], id[2], id[3]]));
+        }
+
+        notes = notes.get(desc + align4(descsz)..)?;
+    }
+
+    None
+}
+
+#[cfg(any(target_os = "linux", target_os = "none"))]
+fn align4(n: usize) -> usize {
+    (n + 3) & !3
+}
@@ -0,0 +1,54 @@
+//! Span enter / exit tracing
+
+use crate::{Buffer, GlobalLog};
+
+#[export_name = "enter {}"]
+#[link_section = ".stlog.internal"]
+pub(crate) static ENTER: u8 = 0;
+
+#[export_name = "exit {}"]
+#[link_section = ".stlog.internal"]
+pub(crate) static EXIT: u8 = 0;
+
+/// A guard that marks the exit of a span when dropped
+///
+/// Created using the [`span!`](macro.span.html) macro.
+#[must_use = "the span is exited as soon as the guard is dropped"]
+pub struct Span {
+    address: Option<u8>,
+}
+
+impl Span {
+    #[doc(hidden)]
+    pub fn enter(address: u8) -> Self {
+        log_span(&ENTER, address);
+
+        Span {
+            address: Some(address),
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn disabled() -> Self {
+        Span { address: None }
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if let Some(address) = self.address {
+            log_span(&EXIT, address);
+        }
+    }
+}
+
+/// Logs the internal message `marker` with the span `address` as its argument
+fn log_span(marker: &'static u8, address: u8) {
+    extern "Rust" {
+        #[link_name = "stlog::GLOBAL_LOGGER"]
+        static LOGGER: &'static dyn GlobalLog;
+    }
+
+    let record = Buffer::message(crate::id(marker), &[u32::from(address)]);
+    unsafe { LOGGER.log_bytes(record.as_slice()) }
+}
@@ -1 +1,54 @@
-pub use stlog_macros::{debug, error, info, trace, warning};
+//! Logging macros that append the location of the invocation to the logged string
+
+#[doc(hidden)]
+pub use stlog_macros::{
+    debug as __debug, error as __error, info as __info, trace as __trace, warning as __warning,
+};
+
+#[doc(inline)]
+pub use crate::{
+    __spanned_debug as debug, __spanned_error as error, __spanned_info as info,
+    __spanned_trace as trace, __spanned_warning as warning,
+};
+
+// NOTE these wrappers pass `$crate` to the procedural macros so that the expansions work when
+// `stlog` is re-exported by another crate, or renamed
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __spanned_error {
+    ($($tt:tt)*) => {
+        $crate::spanned::__error!($crate; $($tt)*)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __spanned_warning {
+    ($($tt:tt)*) => {
+        $crate::spanned::__warning!($crate; $($tt)*)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __spanned_info {
+    ($($tt:tt)*) => {
+        $crate::spanned::__info!($crate; $($tt)*)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __spanned_debug {
+    ($($tt:tt)*) => {
+        $crate::spanned::__debug!($crate; $($tt)*)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __spanned_trace {
+    ($($tt:tt)*) => {
+        $crate::spanned::__trace!($crate; $($tt)*)
+    };
+}
@@ -0,0 +1,141 @@
+//! Per call site state of the rate limited logging macros
+
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use crate::{log_message, log_record, Buffer, GlobalLog, Log};
+
+#[export_name = "{} suppressed {} times"]
+#[link_section = ".stlog.internal"]
+pub(crate) static SUPPRESSED: u8 = 0;
+
+// NOTE these only use atomic loads and stores, which are available on all targets. A message may
+// be logged one extra time if an interrupt handler logs it at the same time
+
+#[doc(hidden)]
+pub struct Once {
+    done: AtomicBool,
+}
+
+impl Once {
+    pub const fn new() -> Self {
+        Once {
+            done: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns `true` the first time it's called
+    pub fn first(&self) -> bool {
+        if self.done.load(Ordering::Relaxed) {
+            false
+        } else {
+            self.done.store(true, Ordering::Relaxed);
+            true
+        }
+    }
+}
+
+#[doc(hidden)]
+pub struct EveryN {
+    count: AtomicU32,
+}
+
+impl EveryN {
+    pub const fn new() -> Self {
+        EveryN {
+            count: AtomicU32::new(0),
+        }
+    }
+
+    /// Returns `true` on the first call and then every `n` calls; an `n` of 0 is treated as 1
+    pub fn tick(&self, n: u32) -> bool {
+        let count = self.count.load(Ordering::Relaxed);
+        let next = count + 1;
+        self.count
+            .store(if next >= n { 0 } else { next }, Ordering::Relaxed);
+        count == 0
+    }
+}
+
+#[doc(hidden)]
+pub struct Throttle {
+    armed: AtomicBool,
+    last: AtomicU32,
+    suppressed: AtomicU32,
+}
+
+impl Throttle {
+    pub const fn new() -> Self {
+        Throttle {
+            armed: AtomicBool::new(false),
+            last: AtomicU32::new(0),
+            suppressed: AtomicU32::new(0),
+        }
+    }
+
+    /// Returns the number of suppressed calls if at least `period` ticks have elapsed since the
+    /// last time the message was logged, or `None` if the message must be suppressed
+    pub fn check(&self, now: u32, period: u32) -> Option<u32> {
+        let elapsed = now.wrapping_sub(self.last.load(Ordering::Relaxed));
+        if self.armed.load(Ordering::Relaxed) && elapsed < period {
+            let suppressed = self.suppressed.load(Ordering::Relaxed);
+            self.suppressed
+                .store(suppressed.saturating_add(1), Ordering::Relaxed);
+            None
+        } else {
+            self.armed.store(true, Ordering::Relaxed);
+            self.last.store(now, Ordering::Relaxed);
+            let suppressed = self.suppressed.load(Ordering::Relaxed);
+            self.suppressed.store(0, Ordering::Relaxed);
+            Some(suppressed)
+        }
+    }
+}
+
+impl Default for Once {
+    fn default() -> Self {
+        Once::new()
+    }
+}
+
+impl Default for EveryN {
+    fn default() -> Self {
+        EveryN::new()
+    }
+}
+
+impl Default for Throttle {
+    fn default() -> Self {
+        Throttle::new()
+    }
+}
+
+/// Logs the message at `address` followed, if `suppressed` is not zero, by a record that says how
+/// many times it was suppressed
+#[doc(hidden)]
+pub fn log_throttled<L>(logger: &mut L, address: u8, suppressed: u32) -> Result<(), L::Error>
+where
+    L: Log + ?Sized,
+{
+    log_record(logger, address)?;
+
+    if suppressed != 0 {
+        log_message(
+            logger,
+            crate::id(&SUPPRESSED),
+            &[u32::from(address), suppressed],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Global version of `log_throttled`
+#[doc(hidden)]
+pub fn global_log_throttled(logger: &dyn GlobalLog, address: u8, suppressed: u32) {
+    logger.log(address);
+
+    if suppressed != 0 {
+        let record = Buffer::message(crate::id(&SUPPRESSED), &[u32::from(address), suppressed]);
+        logger.log_bytes(record.as_slice());
+    }
+}
@@ -0,0 +1,150 @@
+//! Device side timestamps
+
+use crate::{format, uleb128, Buffer, Log};
+
+#[export_name = "timestamp sync {}"]
+#[link_section = ".stlog.internal"]
+static SYNC: u8 = 0;
+
+/// Size, in bytes, of the longest timestamp prefix: a LEB128 encoded `u32`
+const MAX_PREFIX_LEN: usize = 5;
+
+/// A monotonic clock, e.g. a cycle counter, a RTC or a tick counter
+pub trait Timestamp {
+    /// Returns the current time in ticks
+    ///
+    /// The counter is allowed to wrap around
+    fn now(&mut self) -> u32;
+}
+
+/// A logger that prefixes each record with a timestamp
+///
+/// Each record is prefixed with the number of ticks elapsed since the previous record, encoded as
+/// an unsigned LEB128 integer, so messages logged in quick succession only cost one extra byte.
+/// Before the first record an internal "timestamp sync" record that contains the absolute time is
+/// sent; the decoder uses it to reconstruct the absolute device time of each message.
+///
+/// The decoder needs to be told that the stream contains timestamps, and the tick rate of the
+/// clock, e.g. `stlog-decode -e app -t 1000000` for a 1 MHz clock.
+///
+/// The logging macros only call [`Log::begin`](trait.Log.html#method.begin) on local loggers. To
+/// timestamp the records of the global logger, its [`GlobalLog`](trait.GlobalLog.html)
+/// implementation has to call `begin` at the start of each record.
+///
+/// # Example
+///
+/// ```
+/// use stlog::{info, Log, Timestamp, Timestamped};
+///
+/// struct Uart;
+///
+/// impl Log for Uart {
+///     // ..
+/// #   type Error = ();
+/// #
+/// #   fn log(&mut self, _: u8) -> Result<(), ()> {
+/// #       Ok(())
+/// #   }
+/// }
+///
+/// struct CycleCounter;
+///
+/// impl Timestamp for CycleCounter {
+///     fn now(&mut self) -> u32 {
+///         // read the CYCCNT register
+///         // ..
+/// #       0
+///     }
+/// }
+///
+/// let mut logger = Timestamped::new(Uart, CycleCounter);
+///
+/// info!(logger, "Hello, world!").unwrap();
+/// ```
+pub struct Timestamped<L, T> {
+    logger: L,
+    timestamp: T,
+    /// Time at which the last record was sent
+    last: Option<u32>,
+    /// Time at which the record that's about to be sent was started
+    pending: Option<u32>,
+}
+
+impl<L, T> Timestamped<L, T> {
+    /// Wraps the given `logger`; `timestamp` will be used to timestamp the records
+    pub const fn new(logger: L, timestamp: T) -> Self {
+        Timestamped {
+            logger,
+            timestamp,
+            last: None,
+            pending: None,
+        }
+    }
+
+    /// Releases the logger and the clock
+    pub fn free(self) -> (L, T) {
+        (self.logger, self.timestamp)
+    }
+}
+
+impl<L, T> Log for Timestamped<L, T>
+where
+    L: Log,
+    T: Timestamp,
+{
+    type Error = L::Error;
+
+    fn log(&mut self, byte: u8) -> Result<(), L::Error> {
+        self.log_bytes(&[byte])
+    }
+
+    fn begin(&mut self) -> Result<(), L::Error> {
+        let now = self.timestamp.now();
+
+        if self.last.is_none() {
+            let mut sync = Buffer::new();
+            uleb128(&mut sync, 0).ok();
+            sync.extend(Buffer::message(crate::id(&SYNC), &[now]).as_slice())
+                .ok();
+
+            self.logger.begin()?;
+            self.logger.log_bytes(sync.as_slice())?;
+
+            self.last = Some(now);
+        }
+
+        self.pending = Some(now);
+        Ok(())
+    }
+
+    fn log_bytes(&mut self, bytes: &[u8]) -> Result<(), L::Error> {
+        let (now, last) = match (self.pending.take(), self.last) {
+            (Some(now), Some(last)) => (now, last),
+            // not the start of a record
+            _ => return self.logger.log_bytes(bytes),
+        };
+
+        let mut prefix = Buffer::new();
+        uleb128(&mut prefix, now.wrapping_sub(last)).ok();
+        let prefix = prefix.as_slice();
+
+        // NOTE large enough for the longest record the logging macros build, so that buffered
+        // loggers can accept or discard each record as a whole
+        let mut record = [0; MAX_PREFIX_LEN + format::CAPACITY];
+        let len = prefix.len() + bytes.len();
+
+        self.logger.begin()?;
+        if let Some(record) = record.get_mut(..len) {
+            record[..prefix.len()].copy_from_slice(prefix);
+            record[prefix.len()..].copy_from_slice(bytes);
+            self.logger.log_bytes(record)?;
+        } else {
+            // too large to be sent in one go
+            self.logger.log_bytes(prefix)?;
+            self.logger.log_bytes(bytes)?;
+        }
+
+        self.last = Some(now);
+        Ok(())
+    }
+}
@@ -1,6 +1,13 @@
 SECTIONS
 {
   .stlog 0 (INFO) : {
+    *(.stlog.start);
+    *(.stlog.internal);
+
+    __stlog_intern_start__ = .;
+    *(.stlog.intern);
+
+    __stlog_error_start__ = .;
     *(.stlog.error);
 
     __stlog_warning_start__ = .;
@@ -19,3 +26,6 @@ SECTIONS
 
 ASSERT(SIZEOF(.stlog) < 256, "
 ERROR(stlog): stlog only supports up to 256 different strings at the moment.");
+
+/* GNU build ID sent by `stlog::start_session` on targets without an OS */
+PROVIDE(__stlog_build_id = ADDR(.note.gnu.build-id));
@@ -0,0 +1,34 @@
+//! Builds the examples, which deny all warnings, using the 2021 and 2024 editions
+//!
+//! NOTE the expansions of the macros are checked using the edition of the crate that defines them
+//! (2018) so they can keep using `#[export_name]`, `#[link_section]` and `extern` blocks rather than
+//! the `unsafe` forms required by the 2024 edition, which would raise the MSRV to 1.82
+
+use std::{path::Path, process::Command};
+
+fn build_examples(edition: &str) {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    let status = Command::new(env!("CARGO"))
+        .args(["build", "--examples", "--manifest-path"])
+        .arg(root.join("tests/editions").join(edition).join("Cargo.toml"))
+        .env("CARGO_TARGET_DIR", env!("CARGO_TARGET_TMPDIR"))
+        .status()
+        .unwrap();
+
+    assert!(
+        status.success(),
+        "the examples failed to build using the {} edition",
+        edition
+    );
+}
+
+#[test]
+fn edition_2021() {
+    build_examples("2021");
+}
+
+#[test]
+fn edition_2024() {
+    build_examples("2024");
+}
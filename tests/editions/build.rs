@@ -0,0 +1,20 @@
+use std::{env, error::Error, fs, path::PathBuf};
+
+fn main() -> Result<(), Box<dyn Error>> {
+    // same as stlog's build script: make `stlog.x` augment the default linker script
+    let out = PathBuf::from(env::var("OUT_DIR")?);
+    let script = fs::read_to_string("../../../stlog.x")?.replacen(
+        "\n}\n",
+        "\n} INSERT AFTER .comment;\n",
+        1,
+    );
+    fs::write(out.join("stlog.x"), script)?;
+
+    println!(
+        "cargo:rustc-link-arg-examples=-T{}",
+        out.join("stlog.x").display()
+    );
+    println!("cargo:rerun-if-changed=../../../stlog.x");
+
+    Ok(())
+}
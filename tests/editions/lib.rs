@@ -0,0 +1 @@
+//! Empty; only the examples of this package are of interest
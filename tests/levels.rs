@@ -0,0 +1,53 @@
+//! Per-level global loggers
+
+use std::sync::Mutex;
+
+use stlog::{error_throttled, global_logger, info, span, GlobalLog, Level};
+
+struct Logger {
+    records: Mutex<Vec<Vec<u8>>>,
+}
+
+impl Logger {
+    const fn new() -> Self {
+        Logger {
+            records: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.records.lock().unwrap().len()
+    }
+}
+
+impl GlobalLog for Logger {
+    fn log(&self, address: u8) {
+        self.log_bytes(&[address]);
+    }
+
+    fn log_bytes(&self, bytes: &[u8]) {
+        self.records.lock().unwrap().push(bytes.to_vec());
+    }
+}
+
+static ERRORS: Logger = Logger::new();
+
+#[global_logger(error = ERRORS)]
+static LOGGER: Logger = Logger::new();
+
+#[test]
+fn follow_ups_go_with_their_message() {
+    {
+        let _span = span!(Level::Error, "span");
+        info!("Hello");
+    }
+
+    for &now in &[0, 1, 100] {
+        error_throttled!(now, 10, "throttled");
+    }
+
+    // "enter", "exit", "throttled" twice and "suppressed 1 times"
+    assert_eq!(ERRORS.len(), 5);
+    // "Hello"
+    assert_eq!(LOGGER.len(), 1);
+}
@@ -0,0 +1,9 @@
+//! The `null-logger` feature makes the global logger optional
+
+use stlog::{error, info};
+
+#[test]
+fn no_global_logger() {
+    info!("Hello");
+    error!("World");
+}
@@ -0,0 +1,20 @@
+//! Builds a program that uses `stlog` under another name and through a re-export
+
+use std::{path::Path, process::Command};
+
+#[test]
+fn renamed() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    let status = Command::new(env!("CARGO"))
+        .args(["build", "--manifest-path"])
+        .arg(root.join("tests/renamed/Cargo.toml"))
+        .env("CARGO_TARGET_DIR", env!("CARGO_TARGET_TMPDIR"))
+        .status()
+        .unwrap();
+
+    assert!(
+        status.success(),
+        "the program failed to build using a renamed `stlog`"
+    );
+}
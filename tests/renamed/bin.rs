@@ -0,0 +1,53 @@
+//! Logs through an `stlog` that has been renamed and is re-exported by a `hal` module
+
+#![deny(warnings)]
+
+use std::io::{self, Write};
+
+use hal::stlog::{spanned, Format, GlobalLog, Intern, NullLogger};
+
+mod hal {
+    pub use logging as stlog;
+}
+
+struct Logger;
+
+impl GlobalLog for Logger {
+    fn log(&self, byte: u8) {
+        self.log_bytes(&[byte])
+    }
+
+    fn log_bytes(&self, bytes: &[u8]) {
+        io::stdout().write_all(bytes).ok();
+    }
+}
+
+#[hal::stlog::global_logger(crate = hal::stlog)]
+static LOGGER: Logger = Logger;
+
+#[derive(Clone, Copy, Intern)]
+#[stlog(crate = hal::stlog)]
+enum State {
+    Idle,
+    Busy,
+}
+
+#[derive(Format)]
+#[stlog(crate = hal::stlog)]
+struct Sample {
+    state: State,
+    value: u16,
+}
+
+fn main() {
+    hal::stlog::info!("Hello");
+    hal::stlog::info!("state = {}", State::Busy);
+    hal::stlog::warn_once!("Once");
+    spanned::error!("Spanned");
+
+    let sample = Sample {
+        state: State::Idle,
+        value: 1,
+    };
+    hal::stlog::info!(NullLogger, "sample = {}", sample).unwrap();
+}
@@ -0,0 +1,296 @@
+use std::{cell::RefCell, rc::Rc};
+
+use embedded_io_06::ErrorKind;
+use stlog::{
+    info,
+    serial::{hal, io, Error},
+    Format, Log,
+};
+
+/// A serial port that accepts `capacity` bytes before it would block
+#[derive(Clone, Default)]
+struct MockSerial {
+    port: Rc<RefCell<Port>>,
+}
+
+#[derive(Default)]
+struct Port {
+    sent: Vec<u8>,
+    capacity: usize,
+    broken: bool,
+}
+
+impl MockSerial {
+    fn new(capacity: usize) -> Self {
+        let serial = MockSerial::default();
+        serial.port.borrow_mut().capacity = capacity;
+        serial
+    }
+
+    fn sent(&self) -> Vec<u8> {
+        self.port.borrow().sent.clone()
+    }
+
+    fn set_capacity(&self, capacity: usize) {
+        self.port.borrow_mut().capacity = capacity;
+    }
+
+    fn break_(&self) {
+        self.port.borrow_mut().broken = true;
+    }
+}
+
+impl embedded_hal_02::serial::Write<u8> for MockSerial {
+    type Error = ErrorKind;
+
+    fn write(&mut self, byte: u8) -> nb::Result<(), ErrorKind> {
+        let mut port = self.port.borrow_mut();
+        if port.broken {
+            Err(nb::Error::Other(ErrorKind::Other))
+        } else if port.capacity == 0 {
+            Err(nb::Error::WouldBlock)
+        } else {
+            port.capacity -= 1;
+            port.sent.push(byte);
+            Ok(())
+        }
+    }
+
+    fn flush(&mut self) -> nb::Result<(), ErrorKind> {
+        Ok(())
+    }
+}
+
+impl embedded_io_06::ErrorType for MockSerial {
+    type Error = ErrorKind;
+}
+
+impl embedded_io_06::Write for MockSerial {
+    fn write(&mut self, bytes: &[u8]) -> Result<usize, ErrorKind> {
+        let mut port = self.port.borrow_mut();
+        if port.broken {
+            return Err(ErrorKind::Other);
+        }
+
+        let n = port.capacity.min(bytes.len());
+        port.capacity -= n;
+        port.sent.extend_from_slice(&bytes[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<(), ErrorKind> {
+        Ok(())
+    }
+}
+
+impl embedded_io_06::WriteReady for MockSerial {
+    fn write_ready(&mut self) -> Result<bool, ErrorKind> {
+        let port = self.port.borrow();
+        if port.broken {
+            Err(ErrorKind::Other)
+        } else {
+            Ok(port.capacity != 0)
+        }
+    }
+}
+
+#[test]
+fn hal_blocking() {
+    let serial = MockSerial::new(usize::MAX);
+    let mut logger = hal::SerialLogger::new(serial.clone());
+
+    info!(logger, "Blocking write").unwrap();
+    logger.log(1).unwrap();
+    logger.log(2).unwrap();
+
+    assert_eq!(serial.sent().len(), 3);
+    assert_eq!(serial.sent()[1..], [1, 2]);
+}
+
+#[test]
+fn hal_blocking_error() {
+    let serial = MockSerial::new(usize::MAX);
+    let mut logger = hal::SerialLogger::new(serial.clone());
+
+    serial.break_();
+    assert_eq!(info!(logger, "Blocking write error"), Err(ErrorKind::Other));
+}
+
+#[test]
+fn hal_buffered() {
+    let serial = MockSerial::new(2);
+    let mut logger = hal::BufferedSerialLogger::<_, 32>::new(serial.clone());
+
+    for byte in 0..5 {
+        logger.log(byte).unwrap();
+    }
+
+    // `WouldBlock`: the rest of the bytes stay in the buffer
+    assert_eq!(serial.sent(), [0, 1]);
+    assert!(!logger.is_empty());
+
+    serial.set_capacity(usize::MAX);
+    logger.poll().unwrap();
+
+    assert_eq!(serial.sent(), [0, 1, 2, 3, 4]);
+    assert!(logger.is_empty());
+}
+
+#[test]
+fn hal_buffered_full() {
+    let serial = MockSerial::new(0);
+    let mut logger = hal::BufferedSerialLogger::<_, 16>::new(serial.clone());
+
+    info!(logger, "First record").unwrap();
+
+    // there's no room for the largest possible record
+    assert_eq!(info!(logger, "Second record"), Err(Error::BufferFull));
+
+    serial.set_capacity(usize::MAX);
+    info!(logger, "Second record, retried").unwrap();
+
+    assert_eq!(serial.sent().len(), 2);
+}
+
+#[derive(Format)]
+struct Samples(u32, u32, u32, u32, u32);
+
+#[test]
+fn hal_buffered_format() {
+    let serial = MockSerial::new(0);
+    let mut logger = hal::BufferedSerialLogger::<_, 48>::new(serial.clone());
+    let samples = Samples(u32::MAX, u32::MAX, u32::MAX, u32::MAX, u32::MAX);
+
+    // the message ID, the layout ID and 5 bytes per field: 27 bytes
+    info!(logger, "samples = {}", samples).unwrap();
+
+    // there's room to start a record but not for the whole record
+    assert_eq!(
+        info!(logger, "samples, again = {}", samples),
+        Err(Error::BufferFull)
+    );
+
+    serial.set_capacity(usize::MAX);
+    logger.poll().unwrap();
+
+    // only whole records were sent
+    assert_eq!(serial.sent().len(), 27);
+
+    info!(logger, "samples, retried = {}", samples).unwrap();
+    assert_eq!(serial.sent().len(), 2 * 27);
+}
+
+#[derive(Format)]
+struct Wide(u64, u64, u64, u64, u64, u64, u64);
+
+#[test]
+fn hal_blocking_format_too_long() {
+    let serial = MockSerial::new(usize::MAX);
+    let mut logger = hal::SerialLogger::new(serial.clone());
+    let wide = Wide(u64::MAX, 0, 0, u64::MAX, u64::MAX, u64::MAX, u64::MAX);
+
+    // 52 bytes of fields plus two IDs fit in the 64-byte record
+    info!(logger, "wide = {}", wide).unwrap();
+    assert_eq!(serial.sent().len(), 54);
+
+    // 10 bytes per field don't: the record is replaced by "record too long"
+    let wide = Wide(
+        u64::MAX,
+        u64::MAX,
+        u64::MAX,
+        u64::MAX,
+        u64::MAX,
+        u64::MAX,
+        u64::MAX,
+    );
+    info!(logger, "wider = {}", wide).unwrap();
+    assert_eq!(serial.sent()[54..].len(), 1);
+}
+
+#[test]
+fn hal_buffered_error() {
+    let serial = MockSerial::new(0);
+    let mut logger = hal::BufferedSerialLogger::<_, 16>::new(serial.clone());
+
+    logger.log(0).unwrap();
+
+    serial.break_();
+    assert_eq!(logger.poll(), Err(ErrorKind::Other));
+    assert_eq!(
+        info!(logger, "Buffered write error"),
+        Err(Error::Serial(ErrorKind::Other))
+    );
+}
+
+#[test]
+fn io_blocking() {
+    let serial = MockSerial::new(usize::MAX);
+    let mut logger = io::SerialLogger::new(serial.clone());
+
+    info!(logger, "Blocking io write").unwrap();
+    logger.log(1).unwrap();
+    logger.log(2).unwrap();
+
+    assert_eq!(serial.sent().len(), 3);
+    assert_eq!(serial.sent()[1..], [1, 2]);
+}
+
+#[test]
+fn io_blocking_error() {
+    let serial = MockSerial::new(usize::MAX);
+    let mut logger = io::SerialLogger::new(serial.clone());
+
+    serial.break_();
+    assert_eq!(
+        info!(logger, "Blocking io write error"),
+        Err(ErrorKind::Other)
+    );
+}
+
+#[test]
+fn io_buffered() {
+    let serial = MockSerial::new(0);
+    let mut logger = io::BufferedSerialLogger::<_, 16>::new(serial.clone());
+
+    // not ready: everything stays in the buffer
+    for byte in 0..10 {
+        logger.log(byte).unwrap();
+    }
+    assert!(serial.sent().is_empty());
+
+    // partial writes
+    serial.set_capacity(8);
+    logger.poll().unwrap();
+    assert_eq!(serial.sent(), [0, 1, 2, 3, 4, 5, 6, 7]);
+
+    // the queued bytes wrap around the end of the buffer
+    for byte in 10..20 {
+        logger.log(byte).unwrap();
+    }
+
+    serial.set_capacity(usize::MAX);
+    logger.poll().unwrap();
+    assert_eq!(serial.sent(), (0..20).collect::<Vec<_>>());
+    assert!(logger.is_empty());
+}
+
+#[test]
+fn io_buffered_full() {
+    let serial = MockSerial::new(0);
+    let mut logger = io::BufferedSerialLogger::<_, 16>::new(serial.clone());
+
+    info!(logger, "First io record").unwrap();
+    assert_eq!(info!(logger, "Second io record"), Err(Error::BufferFull));
+}
+
+#[test]
+fn io_buffered_error() {
+    let serial = MockSerial::new(0);
+    let mut logger = io::BufferedSerialLogger::<_, 16>::new(serial.clone());
+
+    serial.break_();
+    assert_eq!(
+        info!(logger, "Buffered io write error"),
+        Err(Error::Serial(ErrorKind::Other))
+    );
+}
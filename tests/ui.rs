@@ -0,0 +1,9 @@
+//! Pins down the diagnostics reported when the macros are misused
+
+// NOTE some diagnostics list the implementers of `GlobalLog`, and the `std` feature adds more
+#[cfg_attr(feature = "std", ignore)]
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,8 @@
+use stlog::Format;
+
+#[derive(Format)]
+enum State {
+    Idle,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use stlog::Format;
+
+struct Baud(u32);
+
+#[derive(Format)]
+struct Config {
+    baud: Baud,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use stlog::{global_logger, NullLogger};
+
+static UART: NullLogger = NullLogger;
+
+#[global_logger(error = UART, error = UART)]
+static LOGGER: NullLogger = NullLogger;
+
+fn main() {}
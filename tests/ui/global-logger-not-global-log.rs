@@ -0,0 +1,8 @@
+use stlog::global_logger;
+
+struct Logger;
+
+#[global_logger]
+static LOGGER: Logger = Logger;
+
+fn main() {}
@@ -0,0 +1,6 @@
+use stlog::global_logger;
+
+#[global_logger]
+static mut LOGGER: stlog::NullLogger = stlog::NullLogger;
+
+fn main() {}
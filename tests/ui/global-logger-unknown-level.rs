@@ -0,0 +1,8 @@
+use stlog::{global_logger, NullLogger};
+
+static FATAL: NullLogger = NullLogger;
+
+#[global_logger(fatal = FATAL)]
+static LOGGER: NullLogger = NullLogger;
+
+fn main() {}
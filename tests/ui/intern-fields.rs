@@ -0,0 +1,9 @@
+use stlog::Intern;
+
+#[derive(Intern)]
+enum Error {
+    Timeout,
+    Nack(u8),
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use stlog::Intern;
+
+#[derive(Intern)]
+struct State;
+
+fn main() {}
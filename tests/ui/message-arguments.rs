@@ -0,0 +1,10 @@
+use stlog::{info, Intern};
+
+#[derive(Clone, Copy, Intern)]
+enum State {
+    Idle,
+}
+
+fn main() {
+    info!("{} -> {}", State::Idle);
+}
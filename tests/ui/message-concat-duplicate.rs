@@ -0,0 +1,6 @@
+use stlog::{info, NullLogger};
+
+fn main() {
+    info!(NullLogger, "Hello, world").unwrap();
+    info!(NullLogger, concat!("Hello", ", ", "world")).unwrap();
+}
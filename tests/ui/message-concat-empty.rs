@@ -0,0 +1,5 @@
+use stlog::debug;
+
+fn main() {
+    debug!(concat!("", ""));
+}
@@ -0,0 +1,6 @@
+use stlog::{info, trace_once, NullLogger};
+
+fn main() {
+    info!(NullLogger, "Hello").unwrap();
+    trace_once!("Hello");
+}
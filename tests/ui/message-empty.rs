@@ -0,0 +1,5 @@
+use stlog::{error, NullLogger};
+
+fn main() {
+    error!(NullLogger, "").unwrap();
+}
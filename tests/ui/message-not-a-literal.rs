@@ -0,0 +1,7 @@
+use stlog::{info, NullLogger};
+
+const MESSAGE: &str = "Hello";
+
+fn main() {
+    info!(NullLogger, MESSAGE).unwrap();
+}
@@ -0,0 +1,5 @@
+use stlog::warn;
+
+fn main() {
+    warn!(42);
+}
@@ -0,0 +1,5 @@
+use stlog::info;
+
+fn main() {
+    info!("battery = {}", 42u8);
+}